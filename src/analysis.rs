@@ -1,26 +1,27 @@
-use crate::pokemon::Pokemon;
-use autoscale::AutoScale;
+use crate::{pokemon::Pokemon, ruleset::Ruleset, typing::TeraType};
 use itertools::Itertools;
-use rand::Rng;
-use scoring::{dominates, is_better};
+use rand::{seq::SliceRandom, Rng};
+use scoring::is_better;
 pub mod autoscale;
 pub mod checks;
 pub mod complement_cycle;
 pub mod complement_matrix;
+pub mod coverage_moves;
 pub mod offensive_coverage;
 pub mod resistance;
 pub mod resistance_connector;
 pub mod scoring;
+pub mod speed;
 
-pub fn score<const N: usize>(team: &Vec<Pokemon>) -> [f64; N] {
+pub fn score<const N: usize>(team: &[Pokemon], rules: &Ruleset) -> [f64; N] {
     let mut ret: [f64; N] = [0.0; N];
-    ret[0] = resistance::per_type_net_resist_weak_count(team);
+    ret[0] = resistance::per_type_net_resist_weak_count(team, rules);
     // ret[1] = resistance::one_resist_for_each_type(team);
     // ret[2] = resistance::per_type_multiplier(team, 0.25);
-    // let random_pool = Pokemon::random_team(Pokemon::all_unique_type_chart(), 100).into_iter().collect();
+    // let random_pool = Pokemon::random_team(Pokemon::all_unique_type_chart(rules.generation()), 100).into_iter().collect();
     // ret[3] = checks::counter_count(team, &random_pool) as f64;
-    ret[1] = offensive_coverage::offensive_coverage(team);
-    ret[2] = -(checks::counter_balance(team).len() as f64);
+    ret[1] = offensive_coverage::move_coverage(team, rules);
+    ret[2] = -(checks::counter_balance(team, rules).len() as f64);
 
     // Require specific Pokemon
     ret[3] = ["Excadrill", "Wingull", "Manectric"]
@@ -29,23 +30,86 @@ pub fn score<const N: usize>(team: &Vec<Pokemon>) -> [f64; N] {
     ret
 }
 
-pub fn random_neighbour(team: Vec<Pokemon>, pool: &Vec<Pokemon>) -> Vec<Pokemon> {
+/// The team after its members' form changes, like Mega Evolution
+pub fn transformed_team(team: &[Pokemon]) -> Vec<Pokemon> {
+    team.iter().map(|p| p.transformed().clone()).collect()
+}
+
+/// Scores averaged over the team before and after its form change. `None` when more than one
+/// member has a form change, as a team can only make one
+pub fn score_both_states<const N: usize>(
+    team: &[Pokemon],
+    score_fn: impl Fn(&[Pokemon]) -> [f64; N],
+) -> Option<[f64; N]> {
+    match team.iter().filter(|p| p.transformed.is_some()).count() {
+        0 => Some(score_fn(team)),
+        1 => {
+            let before = score_fn(team);
+            let after = score_fn(&transformed_team(team));
+            Some(std::array::from_fn(|i| (before[i] + after[i]) / 2.0))
+        }
+        _ => None,
+    }
+}
+
+/// Replace a random member, or with Tera types to choose from, sometimes change a member's Tera type.
+/// With form changes to choose from, sometimes move the team's one form change to another member or drop it
+pub fn random_neighbour(
+    team: Vec<Pokemon>,
+    pool: &[Pokemon],
+    tera_types: &[TeraType],
+    form_changes: &[Pokemon],
+) -> Vec<Pokemon> {
     let mut team = team.clone();
     let mut rng = rand::thread_rng();
+    let index = rng.gen_range(0..team.len());
+
+    if !tera_types.is_empty() && rng.gen_bool(0.5) {
+        team[index].tera = tera_types.choose(&mut rng).copied();
+        return team.into_iter().sorted().collect();
+    }
+
+    if !form_changes.is_empty() && rng.gen_bool(0.25) {
+        let options = team
+            .iter()
+            .enumerate()
+            .flat_map(|(i, p)| {
+                form_changes
+                    .iter()
+                    .filter(|f| p.can_become(f))
+                    .map(move |f| (i, f))
+            })
+            .collect::<Vec<_>>();
+        if let Some(&(i, form)) = options.choose(&mut rng) {
+            let current = team[i]
+                .transformed
+                .as_ref()
+                .is_some_and(|t| t.species_data == form.species_data && t.ability == form.ability);
+            team.iter_mut().for_each(|p| p.transformed = None);
+            if !current {
+                team[i] = team[i].with_form_change(form);
+            }
+            return team.into_iter().sorted().collect();
+        }
+    }
 
+    let same_member = |p: &Pokemon, q: &Pokemon| {
+        p.species == q.species && p.typing == q.typing && p.ability == q.ability
+    };
     let mut replacement = Pokemon::random(pool);
-    while team.contains(&replacement) {
+    while team.iter().any(|p| same_member(p, &replacement)) {
         replacement = Pokemon::random(pool);
     }
-    let index = rng.gen_range(0..team.len());
     team[index] = replacement;
     team.into_iter().sorted().collect()
 }
 
 pub fn simulated_annealing<const N: usize>(
     team: Vec<Pokemon>,
-    pool: &Vec<Pokemon>,
-    score_fn: fn(&Vec<Pokemon>) -> [f64; N],
+    pool: &[Pokemon],
+    tera_types: &[TeraType],
+    form_changes: &[Pokemon],
+    score_fn: impl Fn(&[Pokemon]) -> [f64; N],
 ) -> Vec<Pokemon> {
     let mut team_best = team.clone();
     let mut team_good = team;
@@ -53,8 +117,8 @@ pub fn simulated_annealing<const N: usize>(
     let temp_step = 0.1;
     let k_max = pool.len();
     while temp > 0.0 {
-        for k in 0..k_max {
-            let team_new = random_neighbour(team_good.clone(), pool);
+        for _ in 0..k_max {
+            let team_new = random_neighbour(team_good.clone(), pool, tera_types, form_changes);
             let scores_good = score_fn(&team_good);
             let scores_new = score_fn(&team_new);
 
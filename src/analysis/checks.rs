@@ -1,34 +1,82 @@
-use std::collections::BTreeSet;
+use std::{cmp::Ordering, collections::BTreeSet};
 
-use crate::{pokemon::Pokemon, typing::TypeTrait};
+use crate::{
+    analysis::speed,
+    damage::{best_damage, Damage},
+    pokemon::Pokemon,
+    ruleset::{Profile, Ruleset},
+    typing::Multiplier,
+};
 
-pub fn counters(checker: &Pokemon, checkee: &Pokemon) -> bool {
+pub fn counters(checker: &Pokemon, checkee: &Pokemon, rules: &Ruleset) -> bool {
+    profile_counters(&rules.profile(checker), &rules.profile(checkee))
+}
+
+pub fn checks(checker: &Pokemon, checkee: &Pokemon, rules: &Ruleset) -> bool {
+    profile_checks(&rules.profile(checker), &rules.profile(checkee))
+}
+
+/// `counters`, unless the checker is slower and can be OHKO'd before it attacks
+pub fn speed_counters(checker: &Pokemon, checkee: &Pokemon, rules: &Ruleset) -> bool {
+    counters(checker, checkee, rules) && survives_or_moves_first(checker, checkee, rules)
+}
+
+/// `checks`, unless the checker is slower and can be OHKO'd before it attacks
+pub fn speed_checks(checker: &Pokemon, checkee: &Pokemon, rules: &Ruleset) -> bool {
+    checks(checker, checkee, rules) && survives_or_moves_first(checker, checkee, rules)
+}
+
+/// Whether the checker moves first or survives the hit it takes before attacking, assumed when
+/// either's stats are unknown. A slower checkee moves first only with its priority move, and the
+/// checker's own priority move only counts when it OHKOs
+fn survives_or_moves_first(checker: &Pokemon, checkee: &Pokemon, rules: &Ruleset) -> bool {
+    let survives = |hit: Option<Damage>| hit.is_none_or(|d| d.max() < d.hp);
+    let faster = speed::speed(checker).zip(speed::speed(checkee)).is_some_and(|(a, b)| a > b);
+    match speed::priority(checker).cmp(&speed::priority(checkee)) {
+        Ordering::Less if faster => survives(speed::priority_damage(checkee, checker, rules)),
+        Ordering::Greater
+            if speed::priority_damage(checker, checkee, rules).is_some_and(|d| d.min() >= d.hp) =>
+        {
+            true
+        }
+        _ => faster || survives(best_damage(checkee, checker, rules).map(|(_, d)| d)),
+    }
+}
+
+/// `counters` on precomputed profiles
+pub fn profile_counters(checker: &Profile, checkee: &Profile) -> bool {
+    let taken = checker.taken_from(checkee.pokemon);
+    let dealt = checkee.taken_from(checker.pokemon);
     // Resists all stabs and hits supereffectively
-    checkee.typing.iter().all(|t| checker.defense().get(*t) < 1.0)
-        && checker.typing.iter().any(|t| checkee.defense().get(*t) > 1.0)
+    taken < Multiplier::NEUTRAL && dealt > Multiplier::NEUTRAL
 }
 
-pub fn checks(checker: &Pokemon, checkee: &Pokemon) -> bool {
+/// `checks` on precomputed profiles
+pub fn profile_checks(checker: &Profile, checkee: &Profile) -> bool {
+    let taken = checker.taken_from(checkee.pokemon);
+    let dealt = checkee.taken_from(checker.pokemon);
     // Either resist all stabs and hits at least neutrally
-    (checkee.typing.iter().all(|t| checker.defense().get(*t) < 1.0)
-    && checker.typing.iter().any(|t| checkee.defense().get(*t) >= 1.0))
+    (taken < Multiplier::NEUTRAL && dealt >= Multiplier::NEUTRAL)
     // Or not weak to any stab and hits back supereffectively
-    || (checkee.typing.iter().all(|t| checker.defense().get(*t) <= 1.0)
-        && checker.typing.iter().any(|t| checkee.defense().get(*t) > 1.0))
+    || (taken <= Multiplier::NEUTRAL && dealt > Multiplier::NEUTRAL)
 }
 
-pub fn counter_count(team: &Vec<Pokemon>, pool: &BTreeSet<Pokemon>) -> usize {
+pub fn counter_count(team: &[Pokemon], pool: &BTreeSet<Pokemon>, rules: &Ruleset) -> usize {
+    let team = team.iter().map(|p| rules.profile(p)).collect::<Vec<_>>();
     pool.iter()
+        .map(|p| rules.profile(p))
         .filter(|p1| {
-            team.iter().any(|p2| counters(p2, p1))
+            team.iter().any(|p2| profile_counters(p2, p1))
         })
         .count()
 }
 
-pub fn checks_count(team: &Vec<Pokemon>, pool: &BTreeSet<Pokemon>) -> usize {
+pub fn checks_count(team: &[Pokemon], pool: &BTreeSet<Pokemon>, rules: &Ruleset) -> usize {
+    let team = team.iter().map(|p| rules.profile(p)).collect::<Vec<_>>();
     pool.iter()
+        .map(|p| rules.profile(p))
         .filter(|p1| {
-            team.iter().any(|p2| checks(p2, p1))
+            team.iter().any(|p2| profile_checks(p2, p1))
         })
         .count()
 }
@@ -36,26 +84,73 @@ pub fn checks_count(team: &Vec<Pokemon>, pool: &BTreeSet<Pokemon>) -> usize {
 /// Return uncountered counters
 /// Biased towards Pokemon that have very few counters, e.g. Flying/Ground, Electric/Levitate
 /// Biased towards Pokemon with ice weakness, as ice does not resist any time it is strong against
-pub fn counter_balance(team: &Vec<Pokemon>) -> Vec<Pokemon> {
-    let opposing_checks = Pokemon::all_unique_type_chart().iter().filter(
-        |p| team.iter().any(|t| counters(p, t))
+pub fn counter_balance(team: &[Pokemon], rules: &Ruleset) -> Vec<Pokemon> {
+    let team = team.iter().map(|p| rules.profile(p)).collect::<Vec<_>>();
+    let opposing_checks = rules.opponents().iter().filter(
+        |p| team.iter().any(|t| profile_counters(p, t))
     );
     opposing_checks.into_iter().filter(
-        |p| !team.iter().any(|t| counters(t, p))
-    ).cloned().collect()
+        |p| !team.iter().any(|t| profile_counters(t, p))
+    ).map(|p| p.pokemon.clone()).collect()
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::typing::BasicType::*;
+    use crate::{
+        fixtures::{at, moves, pokemon},
+        item::Item,
+        ruleset::Generation,
+        stats::Nature,
+        typing::{Ability, BasicType::*, TypeChart},
+    };
     #[test]
     fn test_main() {
-        assert!(!checks(&Pokemon::from((Ground, Rock)), &Pokemon::from((Fire, Steel))));
-        assert!(counters(&Pokemon::from((Steel, Ground)), &Pokemon::from(Poison)));
-        assert!(!counters(&Pokemon::from(Water), &Pokemon::from(Ground)));
-        assert!(counters(&Pokemon::from(Grass), &Pokemon::from(Ground)));
-        assert!(!counters(&Pokemon::from(Flying), &Pokemon::from(Ground)));
+        let rules = &Ruleset::default();
+        assert!(!checks(&Pokemon::from((Ground, Rock)), &Pokemon::from((Fire, Steel)), rules));
+        assert!(counters(&Pokemon::from((Steel, Ground)), &Pokemon::from(Poison), rules));
+        assert!(!counters(&Pokemon::from(Water), &Pokemon::from(Ground), rules));
+        assert!(counters(&Pokemon::from(Grass), &Pokemon::from(Ground), rules));
+        assert!(!counters(&Pokemon::from(Flying), &Pokemon::from(Ground), rules));
+    }
+
+    #[test]
+    fn test_attacker_ability() {
+        let rules = &Ruleset::default();
+        let gengar = Pokemon::from((Ghost, Poison));
+        let scrappy = Pokemon { ability: Some(Ability::Scrappy), ..Pokemon::from(Fighting) };
+        assert!(!checks(&Pokemon::from(Fighting), &Pokemon::from((Ghost, Normal)), rules));
+        assert!(checks(&scrappy, &Pokemon::from((Ghost, Normal)), rules));
+        assert!(!checks(&scrappy, &gengar, rules));
+    }
+
+    #[test]
+    fn test_speed_checks() {
+        let rules = &Ruleset::default();
+        let at_level = |pokemon, level| at(pokemon, level, Nature::Hardy);
+        let tangela = pokemon("Tangela");
+        let sandshrew = pokemon("Sandshrew");
+        assert!(speed_counters(&tangela, &sandshrew, rules));
+        // Slower, but survives a resisted Ground hit
+        let scarf = Pokemon { item: Some(Item::ChoiceScarf), ..sandshrew.clone() };
+        assert!(speed_counters(&tangela, &scarf, rules));
+        // Slower and OHKO'd despite the resistance
+        let (weak, strong) = (at_level(&tangela, 5), at_level(&sandshrew, 100));
+        assert!(counters(&weak, &strong, rules));
+        assert!(!speed_counters(&weak, &strong, rules));
+        assert!(!speed_checks(&weak, &strong, rules));
+        // Faster, so it hits first
+        assert!(speed_counters(&at_level(&tangela, 100), &at_level(&sandshrew, 5), rules));
+        // A faster checker only takes the slower checkee's priority move first
+        let scarf = Pokemon { item: Some(Item::ChoiceScarf), ..at_level(&tangela, 20) };
+        let strong = at_level(&sandshrew, 35);
+        let quick = Pokemon { moves: moves(&["Poison Jab", "Quick Attack"]), ..strong.clone() };
+        assert!(speed_counters(&scarf, &quick, rules));
+        let slower = at_level(&tangela, 20);
+        assert!(!speed_counters(&slower, &quick, rules));
+        assert!(!speed_counters(&slower, &Pokemon { moves: moves(&["Poison Jab"]), ..strong }, rules));
+        // Type-only Pokemon keep the plain predicates
+        assert!(speed_counters(&Pokemon::from(Grass), &Pokemon::from(Ground), rules));
     }
 
     #[test]
@@ -64,7 +159,16 @@ mod test {
             Pokemon::from((Flying, Ground)),
             Pokemon::from((Rock, Ghost)),
         ];
-        let balance = counter_balance(&team);
+        let balance = counter_balance(&team, &Ruleset::default());
         println!("{:?}", balance);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_alternate_chart() {
+        // Chart where Water also resists Ground
+        let chart = TypeChart::from_json(r#"{"types": {"Water": {"Ground": 0.5}, "Ground": {"Water": 2.0}}}"#).unwrap();
+        assert!(!counters(&Pokemon::from(Water), &Pokemon::from(Ground), &Ruleset::default()));
+        let rules = Ruleset::new(Generation::default()).with_chart(chart);
+        assert!(counters(&Pokemon::from(Water), &Pokemon::from(Ground), &rules));
+    }
+}
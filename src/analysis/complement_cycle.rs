@@ -1,29 +1,27 @@
-use crate::pokemon::{Pokemon};
+use crate::{pokemon::Pokemon, ruleset::Ruleset};
 use itertools::Itertools;
-use rayon::prelude::*;
-use strum::IntoEnumIterator;
 
 use super::complement_matrix::resistance_complements;
 
-fn compute_cyclic_resistance_complement(team: &Vec<Pokemon>) -> i32 {
+pub fn compute_cyclic_resistance_complement(team: &[Pokemon], rules: &Ruleset) -> i32 {
     let mut score = 0;
     for (poke1, poke2) in team.iter().tuple_windows() {
-        score += resistance_complements(poke1, poke2);
+        score += resistance_complements(poke1, poke2, rules);
     }
-    score += resistance_complements(team.last().unwrap(), team.first().unwrap());
+    score += resistance_complements(team.last().unwrap(), team.first().unwrap(), rules);
     score
 }
 
-fn best_complement_cycle(team: &Vec<Pokemon>, pool: &Vec<Pokemon>, size: usize) -> Vec<Pokemon> {
+pub fn best_complement_cycle(team: &[Pokemon], pool: &[Pokemon], rules: &Ruleset) -> Vec<Pokemon> {
     if team.len() > 5 {
-        return team.clone();
+        return team.to_vec();
     }
     let last_pokemon = team.last().unwrap().clone();
     // Find candidates that complement the last pokemon
     let candidates = pool
         .iter()
         .unique()
-        .map(|p| (p.clone(), resistance_complements(&last_pokemon, p)))
+        .map(|p| (p.clone(), resistance_complements(&last_pokemon, p, rules)))
         .max_set_by_key(|(_, s)| *s)
         .into_iter()
         .map(|(p, _)| p)
@@ -32,13 +30,15 @@ fn best_complement_cycle(team: &Vec<Pokemon>, pool: &Vec<Pokemon>, size: usize)
     let mut best_team = vec![];
     let mut best_score = i32::MIN;
     for candidate in candidates {
-        let new_team = team.iter().cloned().chain(vec![candidate]).collect();
-        let score1 = compute_cyclic_resistance_complement(&new_team);
-        let score2 =
-            compute_cyclic_resistance_complement(&best_complement_cycle(&new_team, pool, size));
+        let new_team = team.iter().cloned().chain(vec![candidate]).collect::<Vec<_>>();
+        let score1 = compute_cyclic_resistance_complement(&new_team, rules);
+        let score2 = compute_cyclic_resistance_complement(
+            &best_complement_cycle(&new_team, pool, rules),
+            rules,
+        );
         if score2 > best_score {
             best_score = score2;
-            best_team = best_complement_cycle(&new_team, pool, size);
+            best_team = best_complement_cycle(&new_team, pool, rules);
         }
         if score1 > best_score {
             best_score = score1;
@@ -51,13 +51,13 @@ fn best_complement_cycle(team: &Vec<Pokemon>, pool: &Vec<Pokemon>, size: usize)
 
 #[cfg(test)]
 mod tests {
-    use crate::typing::BasicType;
+    use crate::{ruleset::Generation, typing::BasicType};
     use super::*;
     #[test]
     #[ignore]
     fn recursive_search_specific_type_complements() {
         use BasicType::*;
-        let team = best_complement_cycle(&vec![Pokemon::from((Steel, Flying))], Pokemon::all(), 2);
+        let team = best_complement_cycle(&[Pokemon::from((Steel, Flying))], Pokemon::all(Generation::default()), &Ruleset::default());
         println!("{team:?}");
     }
 
@@ -65,11 +65,11 @@ mod tests {
     fn resistance_complements_test() {
         use BasicType::*;
         let poke = Pokemon::from((Flying, Steel));
-        // Pokemon::all()
+        // Pokemon::all(Generation::default())
         vec![Pokemon::from((Flying, Steel))]
             .into_iter()
             .unique()
-            .map(|p| (p.clone(), resistance_complements(&poke, &p)))
+            .map(|p| (p.clone(), resistance_complements(&poke, &p, &Ruleset::default())))
             .sorted_by(|(_, s1), (_, s2)| s1.cmp(s2))
             .for_each(|(p, s)| {
                 println!("{s:?} {p:?}");
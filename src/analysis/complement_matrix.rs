@@ -1,31 +1,21 @@
 use std::collections::HashMap;
 use itertools::Itertools;
-use strum::IntoEnumIterator;
-use crate::{pokemon::Pokemon, typing::{BasicType, TypeTrait}};
+use crate::{pokemon::Pokemon, ruleset::Ruleset, typing::Multiplier};
 
-pub fn resistance_complements(poke1: &Pokemon, poke2: &Pokemon) -> i32 {
+pub fn resistance_complements(poke1: &Pokemon, poke2: &Pokemon, rules: &Ruleset) -> i32 {
     // How well poke1 resists weaknesses of poke2
     // Higher is better
-    const IMMUNITY_LOG: i32 = -2; // treat immunity as 1/8 resistance
-    let poke1_def = poke1.defense();
-    let poke2_def = poke2.defense();
+    let poke1_def = rules.defense(poke1);
+    let poke2_def = rules.defense(poke2);
     let mut score = 0;
-    for t in BasicType::iter() {
-        let r1 = if poke1_def.get(t) == 0.0 {
-            IMMUNITY_LOG
-        } else {
-            poke1_def.get(t).log2() as i32
-        };
-        let r2 = if poke2_def.get(t) == 0.0 {
-            IMMUNITY_LOG
-        } else {
-            poke2_def.get(t).log2() as i32
-        };
-        if r2 > 0 {
+    for t in rules.types() {
+        let r1 = poke1_def.get(t);
+        let r2 = poke2_def.get(t);
+        if r2 > Multiplier::NEUTRAL {
             // println!("{t:?} {r1:?} {r2:?}");
-            if r1 == 0 {
+            if r1 == Multiplier::NEUTRAL {
                 score += 1;
-            } else if r1 < 0 {
+            } else if r1 < Multiplier::NEUTRAL {
                 score += 2;
             }
         }
@@ -33,7 +23,10 @@ pub fn resistance_complements(poke1: &Pokemon, poke2: &Pokemon) -> i32 {
     score
 }
 
-pub fn create_complement_matrix(pool: &Vec<Pokemon>) -> HashMap<Pokemon, HashMap<Pokemon, i32>> {
+pub fn create_complement_matrix(
+    pool: &[Pokemon],
+    rules: &Ruleset,
+) -> HashMap<Pokemon, HashMap<Pokemon, i32>> {
     let mut result: HashMap<Pokemon, HashMap<Pokemon, i32>> = HashMap::new();
     let combinations = pool.iter().combinations(2);
     for combination in combinations {
@@ -41,38 +34,50 @@ pub fn create_complement_matrix(pool: &Vec<Pokemon>) -> HashMap<Pokemon, HashMap
         result
             .entry(p1.clone())
             .or_default()
-            .insert(p2.clone(), resistance_complements(p1, p2));
+            .insert(p2.clone(), resistance_complements(p1, p2, rules));
         result
             .entry(p2.clone())
             .or_default()
-            .insert(p1.clone(), resistance_complements(p2, p1));
+            .insert(p1.clone(), resistance_complements(p2, p1, rules));
     }
     result
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::typing::BasicType;
+    use crate::{ruleset::Generation, typing::{Ability, BasicType}};
     use super::*;
 
     #[test]
     fn create_complement_matrix_test() {
         use BasicType::*;
+        let rules = &Ruleset::default();
         let ludicolo = Pokemon::from((Grass, Water));
         let primal_groundon = Pokemon::from((Ground, Fire));
-        assert_eq!(resistance_complements(&ludicolo, &primal_groundon), 4);
-        assert_eq!(resistance_complements(&primal_groundon, &ludicolo), 5);
-        let matrix = create_complement_matrix(&vec![ludicolo, primal_groundon]);
+        assert_eq!(resistance_complements(&ludicolo, &primal_groundon, rules), 4);
+        assert_eq!(resistance_complements(&primal_groundon, &ludicolo, rules), 5);
+        let matrix = create_complement_matrix(&[ludicolo, primal_groundon], rules);
         println!("{matrix:?}");
     }
 
+    #[test]
+    fn dry_skin_fire_weakness() {
+        use BasicType::*;
+        let dry_skin = Pokemon {
+            ability: Some(Ability::DrySkin),
+            ..Pokemon::from(Normal)
+        };
+        // Neutral to Fighting, resists the 5/4 Fire weakness
+        assert_eq!(resistance_complements(&Pokemon::from(Fire), &dry_skin, &Ruleset::default()), 3);
+    }
+
     #[test]
     fn find_complements() {
         use BasicType::*;
         let poke = Pokemon::from(Water);
-        Pokemon::all()
+        Pokemon::all(Generation::default())
             .iter()
-            .map(|p| (p.clone(), resistance_complements(&poke, p)))
+            .map(|p| (p.clone(), resistance_complements(&poke, p, &Ruleset::default())))
             .unique()
             .filter(|(_, score)| *score >= 2)
             .for_each(|p| {
@@ -88,9 +93,9 @@ mod tests {
         //     typing: (Poison, Ghost).into(),
         //     ability: None,
         // }]
-        Pokemon::all()
+        Pokemon::all(Generation::default())
             .iter()
-            .map(|p| (p.clone(), resistance_complements(p, &poke)))
+            .map(|p| (p.clone(), resistance_complements(p, &poke, &Ruleset::default())))
             .unique()
             .filter(|(_, score)| *score >= -50)
             .sorted_by(|(_, s1), (_, s2)| s1.cmp(s2))
@@ -103,19 +108,19 @@ mod tests {
     #[ignore]
     fn find_every_steel_complement() {
         use BasicType::*;
-        Pokemon::all()
+        Pokemon::all(Generation::default())
             .iter()
             .filter(|p| p.typing.contains(Steel))
             .unique()
             .for_each(|poke| {
                 // println!("{poke:?}");
-                Pokemon::all()
+                Pokemon::all(Generation::default())
                     .iter()
                     .unique()
-                    .map(|p| (p.clone(), resistance_complements(poke, p)))
+                    .map(|p| (p.clone(), resistance_complements(poke, p, &Ruleset::default())))
                     .max_set_by_key(|(_, s)| *s)
                     .into_iter()
-                    .for_each(|(p, s)| {
+                    .for_each(|(_p, _s)| {
                         // println!("  {p:?} {s:?}");
                     });
             });
@@ -0,0 +1,156 @@
+use itertools::Itertools;
+use std::collections::BTreeSet;
+
+use crate::{
+    analysis::offensive_coverage::offensive_coverage_impl,
+    damage::{attack_effectiveness, attacking_class, move_attack},
+    learnset::damaging_moves_by_type,
+    pokemon::{Move, Pokemon},
+    ruleset::{Profile, Ruleset},
+    typing::Multiplier,
+};
+
+/// A suggested move and the coverage gaps it hits super effectively
+#[derive(Clone, Debug, PartialEq)]
+pub struct CoverageMove {
+    pub move_: &'static Move,
+    pub fixes: Vec<&'static Pokemon>,
+}
+
+/// Opponents that no STAB of the team hits super effectively, by `offensive_coverage_impl`
+pub fn coverage_gaps<'a>(team: &[Pokemon], rules: &'a Ruleset) -> Vec<&'a Profile<'static>> {
+    let coverage = offensive_coverage_impl(team, rules);
+    rules
+        .opponents()
+        .iter()
+        .filter(|p| coverage[p.pokemon] == 0)
+        .collect()
+}
+
+/// Fewest learnable damaging moves of the member's attacking class that hit every gap it can hit,
+/// the strongest of each type. Empty without a learnset or when no move hits any gap
+pub fn coverage_moves(
+    member: &Pokemon,
+    gaps: &[&Profile<'static>],
+    rules: &Ruleset,
+) -> Vec<CoverageMove> {
+    let Some(by_type) = member
+        .species_data
+        .as_ref()
+        .and_then(|species| damaging_moves_by_type(species, rules.generation()))
+    else {
+        return vec![];
+    };
+    let class = attacking_class(member);
+    let candidates = by_type
+        .into_values()
+        .filter_map(|moves| moves.into_iter().find(|m| m.damage_class == class))
+        .map(|m| {
+            let attack = move_attack(member, m);
+            let fixes = (0..gaps.len())
+                .filter(|&i| {
+                    attack_effectiveness(&attack, m.special(), gaps[i], rules) > Multiplier::NEUTRAL
+                })
+                .collect::<BTreeSet<_>>();
+            (m, fixes)
+        })
+        .filter(|(_, fixes)| !fixes.is_empty())
+        .collect::<Vec<_>>();
+    let coverable = candidates
+        .iter()
+        .flat_map(|(_, fixes)| fixes)
+        .collect::<BTreeSet<_>>();
+    // Smallest set first, the strongest moves among sets of the same size
+    let chosen = (1..=candidates.len())
+        .find_map(|size| {
+            candidates
+                .iter()
+                .combinations(size)
+                .filter(|set| {
+                    set.iter().flat_map(|(_, fixes)| fixes).unique().count() == coverable.len()
+                })
+                .max_by_key(|set| set.iter().map(|(m, _)| m.power.unwrap_or(0)).sum::<u32>())
+        })
+        .unwrap_or_default();
+    chosen
+        .into_iter()
+        .map(|(m, fixes)| CoverageMove {
+            move_: m,
+            fixes: fixes.iter().map(|&i| gaps[i].pokemon).collect(),
+        })
+        .sorted_by_key(|c| std::cmp::Reverse(c.fixes.len()))
+        .collect()
+}
+
+/// Coverage moves of each member against the team's coverage gaps
+pub fn suggest_coverage_moves<'a>(
+    team: &'a [Pokemon],
+    rules: &Ruleset,
+) -> Vec<(&'a Pokemon, Vec<CoverageMove>)> {
+    let gaps = coverage_gaps(team, rules);
+    team.iter()
+        .map(|p| (p, coverage_moves(p, &gaps, rules)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{injest::parse_names, pokemon::DamageClass, ruleset::Generation};
+
+    fn team(names: Vec<&str>) -> Vec<Pokemon> {
+        parse_names(names, Generation::default())
+            .unique_by(|p| p.species.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_coverage_moves() {
+        let rules = &Ruleset::default();
+        let team = team(vec!["Scraggy", "Comfey", "Garchomp"]);
+        let gaps = coverage_gaps(&team, rules);
+        assert!(!gaps.is_empty());
+        let suggestions = suggest_coverage_moves(&team, rules);
+        assert_eq!(suggestions.len(), team.len());
+
+        let (_, scraggy) = &suggestions[0];
+        assert!(!scraggy.is_empty());
+        // Moves of the member's better attacking stat that each fix a gap of their own
+        assert!(scraggy
+            .iter()
+            .all(|c| c.move_.damage_class == DamageClass::Physical));
+        for (i, suggestion) in scraggy.iter().enumerate() {
+            assert!(suggestion
+                .fixes
+                .iter()
+                .all(|f| gaps.iter().any(|g| g.pokemon == *f)));
+            let others = scraggy
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .flat_map(|(_, c)| &c.fixes)
+                .collect::<Vec<_>>();
+            assert!(suggestion.fixes.iter().any(|f| !others.contains(&f)));
+        }
+        let (_, comfey) = &suggestions[1];
+        assert!(comfey
+            .iter()
+            .all(|c| c.move_.damage_class == DamageClass::Special));
+
+        let (_, garchomp) = &suggestions[2];
+        assert!(garchomp
+            .iter()
+            .all(|c| c.move_.damage_class == DamageClass::Physical));
+        let fixes = |name: &str| {
+            let suggestion = garchomp.iter().find(|c| c.move_.name == name).unwrap();
+            suggestion
+                .fixes
+                .iter()
+                .map(|f| f.species.as_str())
+                .collect::<Vec<_>>()
+        };
+        // Rock for the Flying types Ground misses, Fire for the Steel types that resist Dragon
+        assert!(fixes("Stone Edge").contains(&"Charizard"));
+        assert!(fixes("Fire Fang").contains(&"Skarmory"));
+    }
+}
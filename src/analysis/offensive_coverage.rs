@@ -1,26 +1,163 @@
 use std::collections::{BTreeMap, BTreeSet};
-use crate::{analysis::scoring::reversed_elu, pokemon::Pokemon, typing::{BasicType, TypeTrait}};
+use crate::{
+    analysis::scoring::reversed_elu,
+    damage::{attack_effectiveness, damaging_moves, move_attack},
+    pokemon::Pokemon,
+    ruleset::{Profile, Ruleset},
+    typing::{Attack, Multiplier, SpecialMove},
+};
 
 // Score how many types the team is able to hit offensively
-pub fn offensive_coverage(team: &Vec<Pokemon>) -> f64 {
-    let team_stabs = team.iter().flat_map(|poke| poke.typing.iter()).collect::<BTreeSet<_>>();
+pub fn offensive_coverage(team: &[Pokemon], rules: &Ruleset) -> f64 {
+    let team_stabs = team.iter().flat_map(|poke| poke.attacks()).collect::<BTreeSet<_>>();
     let mut score = 0.0;
-    for p in Pokemon::all_unique_type_chart() {
-        let def = p.defense();
-        let count = team_stabs.iter().filter(|stab| def.get(***stab) > 1.0).count();
+    for p in rules.opponents() {
+        let count = team_stabs.iter().filter(|stab| p.taken_from(*stab) > Multiplier::NEUTRAL).count();
         let net = reversed_elu(count as f64);
         score += net;
     }
     score
 }
 
-pub fn offensive_coverage_impl(team: &Vec<Pokemon>) -> BTreeMap<Pokemon, usize> {
-    let team_stabs = team.iter().flat_map(|poke| poke.typing.iter()).collect::<BTreeSet<_>>();
+pub fn offensive_coverage_impl(team: &[Pokemon], rules: &Ruleset) -> BTreeMap<Pokemon, usize> {
+    let team_stabs = team.iter().flat_map(|poke| poke.attacks()).collect::<BTreeSet<_>>();
     let mut score = BTreeMap::new();
-    for p in Pokemon::all_unique_type_chart() {
-        let def = p.defense();
-        let count = team_stabs.iter().filter(|stab| def.get(***stab) > 1.0).count();
-        score.insert(p.clone(), count);
+    for p in rules.opponents() {
+        let count = team_stabs.iter().filter(|stab| p.taken_from(*stab) > Multiplier::NEUTRAL).count();
+        score.insert(p.pokemon.clone(), count);
     }
     score
-}
\ No newline at end of file
+}
+
+/// Distinct attacks from each member's damaging moves, or its STABs when its moves are unknown
+fn team_move_attacks(team: &[Pokemon]) -> BTreeSet<(Attack, Option<SpecialMove>)> {
+    team.iter()
+        .flat_map(|poke| {
+            damaging_moves(poke)
+                .into_iter()
+                .map(move |m| (move_attack(poke, &m), m.special()))
+        })
+        .collect()
+}
+
+fn move_hits<'a>(
+    attacks: &'a BTreeSet<(Attack, Option<SpecialMove>)>,
+    p: &'a Profile,
+    rules: &'a Ruleset,
+) -> impl Iterator<Item = Multiplier> + 'a {
+    attacks
+        .iter()
+        .map(|(attack, special)| attack_effectiveness(attack, *special, p, rules))
+}
+
+// Score how many types the team's movesets hit offensively
+pub fn move_coverage(team: &[Pokemon], rules: &Ruleset) -> f64 {
+    let attacks = team_move_attacks(team);
+    let mut score = 0.0;
+    for p in rules.opponents() {
+        let count = move_hits(&attacks, p, rules).filter(|m| *m > Multiplier::NEUTRAL).count();
+        score += reversed_elu(count as f64);
+    }
+    score
+}
+
+pub fn move_coverage_impl(team: &[Pokemon], rules: &Ruleset) -> BTreeMap<Pokemon, usize> {
+    let attacks = team_move_attacks(team);
+    rules.opponents()
+        .iter()
+        .map(|p| {
+            let count = move_hits(&attacks, p, rules).filter(|m| *m > Multiplier::NEUTRAL).count();
+            (p.pokemon.clone(), count)
+        })
+        .collect()
+}
+
+/// Opponents that none of the team's moves hit at least neutrally
+pub fn walled(team: &[Pokemon], rules: &Ruleset) -> Vec<Pokemon> {
+    let attacks = team_move_attacks(team);
+    rules.opponents()
+        .iter()
+        .filter(|p| move_hits(&attacks, p, rules).all(|m| m < Multiplier::NEUTRAL))
+        .map(|p| p.pokemon.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fixtures::move_,
+        injest::parse_pkhex_dump,
+        pokemon::{DamageClass, Typing},
+        ruleset::Generation,
+        typing::BasicType::*,
+    };
+
+    /// Entry for the opponent of the typing without an ability
+    fn of<T>(entries: impl IntoIterator<Item = (Pokemon, T)>, typing: impl Into<Typing>) -> T {
+        let typing = typing.into();
+        entries
+            .into_iter()
+            .find(|(p, _)| p.typing == typing && p.ability.is_none())
+            .unwrap()
+            .1
+    }
+
+    fn is_walled(walled: &[Pokemon], typing: impl Into<Typing>) -> bool {
+        let typing = typing.into();
+        walled.iter().any(|p| p.typing == typing && p.ability.is_none())
+    }
+
+    #[test]
+    fn test_stab_fallback() {
+        // Without known moves the STABs are used
+        let rules = &Ruleset::default();
+        let team = vec![Pokemon::from(Fire), Pokemon::from((Water, Ground))];
+        assert_eq!(move_coverage_impl(&team, rules), offensive_coverage_impl(&team, rules));
+        assert_eq!(move_coverage(&team, rules), offensive_coverage(&team, rules));
+    }
+
+    #[test]
+    fn test_moves() {
+        let rules = &Ruleset::default();
+        let ice_beam = move_("Ice Beam");
+        assert_eq!(ice_beam.damage_class, DamageClass::Special);
+        // A Fire type with only Ice Beam and a status move covers Dragon but not Grass/Steel
+        let team = vec![Pokemon {
+            moves: vec![ice_beam, move_("Will O Wisp")],
+            ..Pokemon::from(Fire)
+        }];
+        let coverage = move_coverage_impl(&team, rules);
+        assert_eq!(of(coverage.clone(), Dragon), 1);
+        assert_eq!(of(coverage, (Grass, Steel)), 0);
+        let walled = walled(&team, rules);
+        assert!(is_walled(&walled, Water));
+        assert!(!is_walled(&walled, Grass));
+        // Status moves only, so nothing is hit
+        let status = vec![Pokemon {
+            moves: vec![move_("Will O Wisp")],
+            ..Pokemon::from(Fire)
+        }];
+        assert_eq!(super::walled(&status, rules).len(), rules.opponents().len());
+        // Freeze-Dry's exception is kept
+        let freeze_dry = vec![Pokemon {
+            moves: vec![move_("Freeze Dry")],
+            ..Pokemon::from(Ice)
+        }];
+        assert_eq!(of(move_coverage_impl(&freeze_dry, rules), Water), 1);
+        // Variable power moves count too
+        let low_kick = vec![Pokemon {
+            moves: vec![move_("Low Kick")],
+            ..Pokemon::from(Normal)
+        }];
+        assert_eq!(of(move_coverage_impl(&low_kick, rules), Rock), 1);
+    }
+
+    #[test]
+    fn test_dump() {
+        let rules = &Ruleset::new(Generation::Gen2To5);
+        let team = parse_pkhex_dump("Box Data Dump.csv", Generation::Gen2To5);
+        assert!(team.iter().all(|p| !p.moves.is_empty()));
+        assert!(move_coverage(&team, rules) > 0.0);
+    }
+}
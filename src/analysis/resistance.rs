@@ -1,16 +1,14 @@
-use strum::IntoEnumIterator;
-
-use crate::{pokemon::Pokemon, typing::{BasicType, TypeTrait}};
+use crate::{pokemon::Pokemon, ruleset::Ruleset, typing::Multiplier};
 
 use super::scoring::reversed_elu;
 
 /// For every type, compute net number of Pokemon resisting and weak to that type
 /// Biased towards Pokemon with high resistance-to-weakness ratio, e.g. Water/Ground, Bug/Steel
-pub fn one_resist_for_each_type(team: &Vec<Pokemon>) -> f64 {
-    let team_defenses = team.iter().map(|poke| poke.defense()).collect::<Vec<_>>();
+pub fn one_resist_for_each_type(team: &[Pokemon], rules: &Ruleset) -> f64 {
+    let team_defenses = team.iter().map(|poke| rules.defense(poke)).collect::<Vec<_>>();
     let mut score = 0.0;
-    for t in BasicType::iter() {
-        let count = team_defenses.iter().filter(|def| def.get(t) > 1.0).count();
+    for t in rules.types() {
+        let count = team_defenses.iter().filter(|def| def.get(t) > Multiplier::NEUTRAL).count();
         for i in 0..count {
             score += 1.0 / (i + 1) as f64 / (i + 1) as f64;
         }
@@ -20,19 +18,20 @@ pub fn one_resist_for_each_type(team: &Vec<Pokemon>) -> f64 {
 
 /// For every type, score on product of weak/resist multipliers
 /// Biased towards Pokemon with immunities
-pub fn per_type_multiplier(team: &Vec<Pokemon>, immune_multiplier: f64) -> f64 {
-    let team_defenses = team.iter().map(|poke| poke.defense()).collect::<Vec<_>>();
+pub fn per_type_multiplier(team: &[Pokemon], immune_multiplier: f64, rules: &Ruleset) -> f64 {
+    let team_defenses = team.iter().map(|poke| rules.defense(poke)).collect::<Vec<_>>();
     let mut score = 0.0;
-    for t in BasicType::iter() {
-        let mut multiplier = 1.0;
+    for t in rules.types() {
+        let mut multiplier = Multiplier::NEUTRAL;
+        let mut immune_count = 0;
         for def in team_defenses.iter() {
-            if def.get(t) == 0.0 {
-                multiplier *= immune_multiplier;
+            if def.get(t).is_immune() {
+                immune_count += 1;
             } else {
-                multiplier *= def.get(t) as f64;
+                multiplier = multiplier * def.get(t);
             }
         }
-        let normalized = -multiplier.log2();
+        let normalized = -(multiplier.log2() + immune_count as f64 * immune_multiplier.log2());
         let net = reversed_elu(normalized);
         score += net;
     }
@@ -40,12 +39,12 @@ pub fn per_type_multiplier(team: &Vec<Pokemon>, immune_multiplier: f64) -> f64 {
 }
 
 /// For every type, score on difference between the number of resistances and weaknesses
-pub fn per_type_net_resist_weak_count(team: &Vec<Pokemon>) -> f64 {
-    let team_defenses = team.iter().map(|poke| poke.defense()).collect::<Vec<_>>();
+pub fn per_type_net_resist_weak_count(team: &[Pokemon], rules: &Ruleset) -> f64 {
+    let team_defenses = team.iter().map(|poke| rules.defense(poke)).collect::<Vec<_>>();
     let mut score = 0.0;
-    for t in BasicType::iter() {
-        let weak_count = team_defenses.iter().filter(|def| def.get(t) > 1.0).count();
-        let resist_count = team_defenses.iter().filter(|def| def.get(t) < 1.0).count();
+    for t in rules.types() {
+        let weak_count = team_defenses.iter().filter(|def| def.get(t) > Multiplier::NEUTRAL).count();
+        let resist_count = team_defenses.iter().filter(|def| def.get(t) < Multiplier::NEUTRAL).count();
         let diff = resist_count as f64 - weak_count as f64;
         let net = reversed_elu(diff);
         score += net;
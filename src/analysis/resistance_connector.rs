@@ -1,4 +1,4 @@
-use crate::pokemon::Pokemon;
+use crate::{pokemon::Pokemon, ruleset::Ruleset};
 
 use super::complement_matrix::resistance_complements;
 
@@ -7,11 +7,12 @@ pub fn resistance_connector(
     poke1: &Pokemon,
     poke2: &Pokemon,
     pool: &[Pokemon],
+    rules: &Ruleset,
 ) -> Vec<(Pokemon, i32, i32)> {
     pool.iter()
         .map(|poke3| {
-            let score1 = resistance_complements(poke3, poke1);
-            let score2 = resistance_complements(poke2, poke3);
+            let score1 = resistance_complements(poke3, poke1, rules);
+            let score2 = resistance_complements(poke2, poke3, rules);
             (poke3.clone(), score1, score2)
         })
         .collect()
@@ -20,15 +21,15 @@ pub fn resistance_connector(
 #[cfg(test)]
 mod tests {
     
-    use crate::typing::BasicType::*;
+    use crate::{ruleset::Generation, typing::BasicType::*};
     use super::*;
 
     #[test]
     fn test_resistance_connector() {
-        let pool = Pokemon::all_type_combinations().collect::<Vec<_>>();
+        let pool = Pokemon::all_type_combinations(Generation::default()).collect::<Vec<_>>();
         let poke1 = Pokemon::from(Normal);
         let poke2 = Pokemon::from(Fire);
-        let res = resistance_connector(&poke1, &poke2, &pool);
+        let res = resistance_connector(&poke1, &poke2, &pool, &Ruleset::default());
         assert!(res.len() >= 153);
     }
 }
\ No newline at end of file
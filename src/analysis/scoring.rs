@@ -1,6 +1,6 @@
 pub fn reversed_elu(input: f64) -> f64 {
     let base = f64::exp(1.0);
-    return -base.powf(-input) + 1.0;
+    -base.powf(-input) + 1.0
 }
 
 /// Check if score1 dominates score2,
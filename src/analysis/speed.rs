@@ -0,0 +1,120 @@
+use itertools::Itertools;
+
+use crate::{
+    damage::{battle_stats, damage, damaging_moves, Damage},
+    pokemon::Pokemon,
+    ruleset::Ruleset,
+};
+
+/// Speed in battle after the held item, `None` without species data
+pub fn speed(pokemon: &Pokemon) -> Option<u32> {
+    let speed = battle_stats(pokemon)?.speed as u32;
+    Some(match pokemon.item {
+        Some(item) => item.speed_multiplier().apply(speed),
+        None => speed,
+    })
+}
+
+/// Highest priority among the Pokemon's damaging moves
+pub fn priority(pokemon: &Pokemon) -> i8 {
+    damaging_moves(pokemon)
+        .iter()
+        .map(|m| m.priority)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Strongest damage among the attacker's highest priority damaging moves, `None` without any
+pub fn priority_damage(attacker: &Pokemon, defender: &Pokemon, rules: &Ruleset) -> Option<Damage> {
+    let priority = priority(attacker);
+    damaging_moves(attacker)
+        .into_iter()
+        .filter(|m| m.priority == priority)
+        .filter_map(|m| damage(attacker, &m, defender, rules))
+        .max_by_key(|d| d.max())
+}
+
+/// Whether `a` hits before `b`, by priority and then Speed. Speed ties count as not moving first.
+/// `None` when either Speed is unknown
+pub fn moves_first(a: &Pokemon, b: &Pokemon) -> Option<bool> {
+    if priority(a) != priority(b) {
+        return Some(priority(a) > priority(b));
+    }
+    Some(speed(a)? > speed(b)?)
+}
+
+/// Members grouped by Speed, fastest first. Members without a known Speed are left out
+pub fn speed_tiers(team: &[Pokemon]) -> Vec<(u32, Vec<&Pokemon>)> {
+    team.iter()
+        .filter_map(|p| Some((speed(p)?, p)))
+        .sorted_by_key(|(speed, _)| std::cmp::Reverse(*speed))
+        .chunk_by(|(speed, _)| *speed)
+        .into_iter()
+        .map(|(speed, tier)| (speed, tier.map(|(_, p)| p).collect()))
+        .collect()
+}
+
+/// Threats that move before every member of the team
+pub fn outspeeding_threats<'a>(team: &[Pokemon], threats: &'a [Pokemon]) -> Vec<&'a Pokemon> {
+    threats
+        .iter()
+        .filter(|threat| team.iter().all(|p| moves_first(threat, p) == Some(true)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fixtures::{at, move_, pokemon},
+        item::Item,
+        stats::Nature,
+        typing::BasicType,
+    };
+
+    #[test]
+    fn test_speed() {
+        // Base 102 at level 50 with 31 IVs
+        let garchomp = pokemon("Garchomp");
+        assert_eq!(speed(&garchomp), Some(122));
+        let scarf = Pokemon {
+            item: Some(Item::ChoiceScarf),
+            ..garchomp.clone()
+        };
+        assert_eq!(speed(&scarf), Some(183));
+        let jolly = at(&garchomp, 50, Nature::Jolly);
+        assert_eq!(speed(&jolly), Some(134));
+        assert_eq!(speed(&Pokemon::from(BasicType::Fire)), None);
+
+        let jolteon = pokemon("Jolteon");
+        assert_eq!(moves_first(&jolteon, &garchomp), Some(true));
+        assert_eq!(moves_first(&garchomp, &jolteon), Some(false));
+        assert_eq!(moves_first(&scarf, &jolteon), Some(true));
+        assert_eq!(moves_first(&garchomp, &garchomp), Some(false));
+
+        let team = [garchomp.clone(), jolteon.clone(), garchomp.clone()];
+        let tiers = speed_tiers(&team);
+        assert_eq!(tiers.len(), 2);
+        assert_eq!(tiers[0].1, vec![&jolteon]);
+        assert_eq!(tiers[1].1.len(), 2);
+    }
+
+    #[test]
+    fn test_priority() {
+        let garchomp = pokemon("Garchomp");
+        let jolteon = pokemon("Jolteon");
+        let priority = Pokemon {
+            moves: vec![move_("Extreme Speed")],
+            ..garchomp.clone()
+        };
+        assert_eq!(super::priority(&priority), 2);
+        assert_eq!(moves_first(&priority, &jolteon), Some(true));
+
+        let threats = vec![jolteon.clone(), garchomp.clone()];
+        assert_eq!(
+            outspeeding_threats(std::slice::from_ref(&garchomp), &threats),
+            vec![&jolteon]
+        );
+        assert!(outspeeding_threats(&[priority], &threats).is_empty());
+    }
+}
@@ -6,15 +6,19 @@ use rayon::iter::ParallelIterator;
 use std::collections::BTreeSet;
 use std::sync::{Arc, Mutex};
 use type_theory::analysis::autoscale::AutoScale;
-use type_theory::analysis::scoring::{dominates, is_better};
-use type_theory::analysis::{checks, offensive_coverage, resistance, score, simulated_annealing};
-use type_theory::injest::{parse_names_file, parse_pkhex_dump};
+use type_theory::analysis::{score, score_both_states, simulated_annealing};
+use type_theory::evolution::expand_pool;
+use type_theory::field::{Field, Terrain, Weather};
+use type_theory::injest::parse_names_file;
 use type_theory::pokemon::{Pokemon, PokemonIteratorHelper};
+use type_theory::ruleset::{Generation, Ruleset};
+use type_theory::typing::TypeChart;
 use clap::Parser;
 
+#[allow(dead_code)]
 fn compute_best_team<const N: usize>(
     autoscale: &AutoScale<N>,
-    score: fn(&Vec<Pokemon>) -> [f64; N],
+    score: impl Fn(&[Pokemon]) -> [f64; N],
     teams: &BTreeSet<Vec<Pokemon>>,
 ) -> (Vec<Pokemon>, [f64; N]) {
     let mut best_team = Vec::new();
@@ -33,7 +37,7 @@ fn compute_best_team<const N: usize>(
 }
 
 fn discard_dominated_teams<const N: usize>(
-    score: fn(&Vec<Pokemon>) -> [f64; N],
+    score: impl Fn(&[Pokemon]) -> [f64; N],
     teams: &BTreeSet<Vec<Pokemon>>,
 ) -> BTreeSet<Vec<Pokemon>> {
     // If another team dominates a team in all scores, discard the dominated team
@@ -73,7 +77,37 @@ fn discard_dominated_teams<const N: usize>(
 #[command(version, about, long_about=None)]
 struct Cli {
     #[arg(short, long)]
-    path: Option<PathBuf>
+    path: Option<PathBuf>,
+    /// One of gen1, gen2-5, gen6+
+    #[arg(short, long, default_value_t = Generation::default())]
+    generation: Generation,
+    /// JSON type chart to use instead of the generation's one
+    #[arg(short, long)]
+    chart: Option<PathBuf>,
+    /// Build for Inverse Battles
+    #[arg(short, long)]
+    inverse: bool,
+    /// Let the optimizer choose a Tera type for each member
+    #[arg(short, long)]
+    tera: bool,
+    /// Let the optimizer give one member a Mega or Primal form, scoring the team before and after
+    #[arg(short, long)]
+    mega: bool,
+    /// Add what the listed Pokemon evolve into by this level
+    #[arg(short, long)]
+    level_cap: Option<u8>,
+    /// Count on evolution items like stones when evolving by the level cap
+    #[arg(long)]
+    evolution_items: bool,
+    /// One of rain, sun, heavy-rain, harsh-sun, sandstorm, snow, strong-winds
+    #[arg(short, long)]
+    weather: Option<Weather>,
+    /// One of electric, grassy, psychic, misty
+    #[arg(long)]
+    terrain: Option<Terrain>,
+    /// Build under Gravity
+    #[arg(long)]
+    gravity: bool,
 }
 
 fn main() {
@@ -83,9 +117,39 @@ fn main() {
     const THREAD_COUNT: usize = 6;
 
     const SCORES_COUNT: usize = 4;
+    let rules = match cli.chart {
+        Some(path) => match TypeChart::from_path(&path) {
+            Ok(chart) => Ruleset::new(cli.generation).with_chart(chart),
+            Err(e) => {
+                eprintln!("Could not load type chart {path:?}: {e}");
+                std::process::exit(1);
+            }
+        },
+        None => Ruleset::new(cli.generation),
+    };
+    let rules = if cli.inverse { rules.inverse() } else { rules };
+    let rules = rules.with_field(Field {
+        weather: cli.weather,
+        terrain: cli.terrain,
+        gravity: cli.gravity,
+    });
+    let tera_types = if cli.tera { rules.tera_types() } else { vec![] };
+    // random_neighbour plans at most one form change, rank any other team last
+    let score = |team: &[Pokemon]| {
+        score_both_states(team, |team| score::<SCORES_COUNT>(team, &rules))
+            .unwrap_or([f64::NEG_INFINITY; SCORES_COUNT])
+    };
     let team_size = 6;
     let pool = {
-        let pool = parse_names_file(cli.path.unwrap_or_else(|| panic!("No path provided")))
+        let pool = parse_names_file(
+            cli.path.unwrap_or_else(|| panic!("No path provided")),
+            cli.generation,
+        );
+        let pool = match cli.level_cap {
+            Some(level_cap) => expand_pool(&pool, cli.generation, level_cap, cli.evolution_items),
+            None => pool,
+        };
+        let pool = pool
             .into_iter()
             .unique_by_type_ability()
             .collect::<Vec<_>>();
@@ -94,6 +158,15 @@ fn main() {
         pool
     };
     eprintln!("Pool size: {}", pool.len());
+    let form_changes = if cli.mega {
+        Pokemon::battle_forms(cli.generation)
+            .into_iter()
+            .filter(|form| pool.iter().any(|p| p.can_become(form)))
+            .collect()
+    } else {
+        vec![]
+    };
+    eprintln!("Form changes: {}", form_changes.len());
 
     let best_teams = Arc::new(Mutex::new(BTreeSet::new()));
 
@@ -102,25 +175,27 @@ fn main() {
         .build_global()
         .unwrap();
 
-    let counter = Arc::new(Mutex::new(1));
+    let counter = Arc::new(Mutex::new(1usize));
     rayon::iter::repeatn((), SIMULATED_ANNEALING_ITERATIONS).for_each(|_| {
         let team = simulated_annealing(
             Pokemon::random_team(&pool, team_size),
             &pool,
-            score::<SCORES_COUNT>,
+            &tera_types,
+            &form_changes,
+            score,
         );
         best_teams.lock().unwrap().insert(team);
         let mut counter = counter.lock().unwrap();
         *counter += 1;
 
-        if *counter % THREAD_COUNT == 0 || *counter == SIMULATED_ANNEALING_ITERATIONS {
+        if (*counter).is_multiple_of(THREAD_COUNT) || *counter == SIMULATED_ANNEALING_ITERATIONS {
             let mut best_teams = best_teams.lock().unwrap();
-            *best_teams = discard_dominated_teams(score::<SCORES_COUNT>, &best_teams);
+            *best_teams = discard_dominated_teams(score, &best_teams);
             eprintln!("{counter:?}:");
             best_teams
                 .iter()
                 .map(|team| {
-                    let scores = score::<SCORES_COUNT>(team);
+                    let scores = score(team);
                     (scores, team)
                 })
                 .sorted_by(|(scores1, _), (scores2, _)| {
@@ -131,9 +206,18 @@ fn main() {
                 .for_each(|(scores, team)| {
                     eprint!("{scores:7.3?} ");
                     team.iter()
-                        .map(|p| &p.species)
+                        .map(|p| {
+                            let name = match p.tera {
+                                Some(tera) => format!("{:?} ({tera:?})", p.species),
+                                None => format!("{:?}", p.species),
+                            };
+                            match &p.transformed {
+                                Some(form) => format!("{name} -> {:?}", form.name()),
+                                None => name,
+                            }
+                        })
                         .sorted()
-                        .for_each(|p| eprint!("{:?} ", p));
+                        .for_each(|p| eprint!("{p} "));
                     eprintln!();
                     println!("{}", serde_json::to_string(&team).unwrap());
                 });
@@ -149,11 +233,11 @@ mod test {
     #[test]
     fn test_score_trio_cores() {
         let team = vec![
-            Pokemon::from((Fire)),
-            Pokemon::from((Water)),
-            Pokemon::from((Grass)),
+            Pokemon::from(Fire),
+            Pokemon::from(Water),
+            Pokemon::from(Grass),
         ];
-        let scores = score::<5>(&team);
+        let scores = score::<5>(&team, &Ruleset::default());
         println!("{scores:?}");
     }
 
@@ -162,12 +246,12 @@ mod test {
         let team = vec![
             Pokemon::from((Water, Flying)),
             Pokemon::from((Grass, Steel)),
-            Pokemon::from((Fire)),
-            Pokemon::from((Water)),
+            Pokemon::from(Fire),
+            Pokemon::from(Water),
             Pokemon::from((Dragon, Water)),
             Pokemon::from((Dragon, Ghost)),
         ];
-        let scores = score::<5>(&team);
+        let scores = score::<5>(&team, &Ruleset::default());
         println!("{scores:?}");
     }
 
@@ -178,7 +262,7 @@ mod test {
         let teams: BTreeSet<Vec<Pokemon>> = BTreeSet::from([
             parse_names(vec![
                 "Beldum", "Comfey", "Ducklett", "Geodude", "Houndour", "Pansage",
-            ])
+            ], Generation::default())
             .collect(),
             parse_names(vec![
                 "Cutiefly",
@@ -187,12 +271,12 @@ mod test {
                 "Electrike",
                 "Houndour",
                 "Nidoran♂ (male)",
-            ])
+            ], Generation::default())
             .collect(),
         ]);
-        let after = discard_dominated_teams(score::<5>, &teams);
+        let after = discard_dominated_teams(|team| score::<5>(team, &Ruleset::default()), &teams);
         for team in &after {
-            eprint!("{:?}: ", score::<5>(team));
+            eprint!("{:?}: ", score::<5>(team, &Ruleset::default()));
             team.iter().for_each(|p| eprint!("{:?} ", p.species));
             eprintln!();
         }
@@ -205,7 +289,7 @@ mod test {
         let teams: BTreeSet<Vec<Pokemon>> = BTreeSet::from([
             parse_names(vec![
                 "Beldum", "Comfey", "Ducklett", "Geodude", "Houndour", "Pansage",
-            ])
+            ], Generation::default())
             .collect(),
             parse_names(vec![
                 "Beldum",
@@ -214,10 +298,10 @@ mod test {
                 "Geodude",
                 "Houndour",
                 "Rillaboom",
-            ])
+            ], Generation::default())
             .collect(),
         ]);
-        let after = discard_dominated_teams(score::<5>, &teams);
+        let after = discard_dominated_teams(|team| score::<5>(team, &Ruleset::default()), &teams);
         assert_eq!(after.len(), 2); // Keep both teams if identical scores but different members
     }
 
@@ -227,14 +311,55 @@ mod test {
         let teams: BTreeSet<Vec<Pokemon>> = BTreeSet::from([
             parse_names(vec![
                 "Beldum", "Comfey", "Ducklett", "Geodude", "Houndour", "Pansage",
-            ])
+            ], Generation::default())
             .collect(),
             parse_names(vec![
                 "Beldum", "Comfey", "Ducklett", "Geodude", "Houndour", "Pansage",
-            ])
+            ], Generation::default())
             .collect(),
         ]);
-        let after = discard_dominated_teams(score::<5>, &teams);
+        let after = discard_dominated_teams(|team| score::<5>(team, &Ruleset::default()), &teams);
         assert_eq!(after.len(), 1); // Keep both teams if identical scores but different members
     }
+
+    #[test]
+    fn test_one_form_change() {
+        use type_theory::analysis::{random_neighbour, transformed_team};
+        let generation = Generation::Gen6Plus;
+        let team: Vec<Pokemon> = parse_names(
+            vec!["Charizard", "Altaria", "Garchomp", "Ducklett", "Houndour", "Comfey"],
+            generation,
+        )
+        .unique_by(|p| p.species.clone())
+        .collect();
+        let form_changes = Pokemon::battle_forms(generation);
+        let mut neighbour = team.clone();
+        for _ in 0..200 {
+            neighbour = random_neighbour(
+                neighbour,
+                Pokemon::all(generation),
+                &[],
+                &form_changes,
+            );
+            assert!(neighbour.iter().filter(|p| p.transformed.is_some()).count() <= 1);
+        }
+
+        let altaria = team.iter().position(|p| p.species == "Altaria").unwrap();
+        let mut mega = team.clone();
+        let form = form_changes.iter().find(|f| mega[altaria].can_become(f)).unwrap();
+        mega[altaria] = mega[altaria].with_form_change(form);
+        let rules = Ruleset::default();
+        let before = score::<5>(&team, &rules);
+        let after = score::<5>(&transformed_team(&mega), &rules);
+        let both = score_both_states(&mega, |team| score::<5>(team, &rules)).unwrap();
+        for i in 0..5 {
+            assert_eq!(both[i], (before[i] + after[i]) / 2.0);
+        }
+        assert_eq!(score_both_states(&team, |team| score::<5>(team, &rules)), Some(before));
+        // A team can only make one form change
+        let charizard = team.iter().position(|p| p.species == "Charizard").unwrap();
+        let form = form_changes.iter().find(|f| mega[charizard].can_become(f)).unwrap();
+        mega[charizard] = mega[charizard].with_form_change(form);
+        assert_eq!(score_both_states(&mega, |team| score::<5>(team, &rules)), None);
+    }
 }
@@ -1,37 +1,63 @@
+use itertools::Itertools;
 use type_theory::{
     analysis::checks::counters,
-    pokemon::Pokemon,
-    typing::{BasicType, TypeTrait},
+    damage::{damage, damaging_moves, Damage},
+    injest::parse_names,
+    learnset::with_potential_moves,
+    pokemon::{Move, Pokemon},
+    ruleset::{Generation, Ruleset},
+    typing::{AttackTrait, BasicType, Multiplier},
 };
 
-/// Given a team, finds appropriate checks for an opposing Pokemon
+/// Given a team, finds appropriate checks for an opposing Pokemon, most damaging first
 fn main() {
-    let team: Vec<Pokemon> = serde_json::from_str(r#"[{"species":"Comfey","typing":["Fairy"],"ability":null,"moves":[]},{"species":"Ducklett","typing":["Water","Flying"],"ability":null,"moves":[]},{"species":"Inkay","typing":["Psychic","Dark"],"ability":null,"moves":[]},{"species":"Electrike","typing":["Electric"],"ability":null,"moves":[]},{"species":"Wingull","typing":["Water","Flying"],"ability":null,"moves":[]},{"species":"Beldum","typing":["Psychic","Steel"],"ability":null,"moves":[]}]"#).unwrap();
+    let team: Vec<Pokemon> = parse_names(
+        vec![
+            "Comfey",
+            "Ducklett",
+            "Inkay",
+            "Electrike",
+            "Wingull",
+            "Beldum",
+        ],
+        Generation::default(),
+    )
+    .unique_by(|p| p.species.clone())
+    .map(|p| with_potential_moves(&p, Generation::default()))
+    .collect();
 
-    use BasicType::*;
-    let opposing_pokemon = Pokemon::from((Ground));
+    let rules = Ruleset::default();
+    let chart = rules.chart();
+    let opposing_pokemon = parse_names(vec!["Sandshrew"], Generation::default())
+        .next()
+        .unwrap();
+    let damages = |p: &Pokemon| -> Vec<(Move, Damage)> {
+        damaging_moves(p)
+            .into_iter()
+            .filter_map(|m| damage(p, &m, &opposing_pokemon, &rules).map(|d| (m, d)))
+            .sorted_by_key(|(_, d)| std::cmp::Reverse(d.max()))
+            .collect()
+    };
     team.iter()
-        .filter(|p| counters(p, &opposing_pokemon))
-        .for_each(|p| {
-            let stab_resistance: Vec<(BasicType, f32)> = opposing_pokemon
-                .typing
-                .iter()
-                .map(|t| (*t, p.defense().get(*t)))
+        .filter(|p| counters(p, &opposing_pokemon, &rules))
+        .map(|p| (p, damages(p)))
+        .sorted_by_key(|(_, damages)| std::cmp::Reverse(damages.first().map(|(_, d)| d.max())))
+        .for_each(|(p, damages)| {
+            let stab_resistance: Vec<(BasicType, Multiplier)> = opposing_pokemon
+                .stabs()
+                .into_iter()
+                .map(|t| (t, t.effectiveness_with(p, chart)))
                 .collect();
             println!("{:?} {:?}", p.species, stab_resistance);
-            if p.moves.is_empty() {
-                for t in p.typing.iter() {
-                    if opposing_pokemon.defense().get(*t) > 1.0 {
-                        println!("  {:?} STAB", t);
-                    }
-                }
-            } else {                
-                for move_ in p.moves.iter().filter(|m| match m.power {
-                    Some(_) => opposing_pokemon.defense().get(m.typing) > 1.0,
-                    None => false,
-                }) {
-                    println!("  {:?}", move_);
-                }
+            for (move_, damage) in damages {
+                println!(
+                    "  {}: {:.1}% - {:.1}%, {:.0}% to OHKO, {:.0}% to 2HKO",
+                    move_.name,
+                    damage.min_percent(),
+                    damage.max_percent(),
+                    100.0 * damage.ko_chance(1),
+                    100.0 * damage.ko_chance(2),
+                );
             }
             println!();
         });
@@ -0,0 +1,212 @@
+use clap::Parser;
+use itertools::Itertools;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+use type_theory::{
+    learnset::{LearnMethod, Learnable},
+    pokemon::Move,
+    ruleset::Generation,
+    species::Species,
+};
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about=None)]
+struct Cli {
+    /// Directory of PokeAPI's CSV tables, `data/v2/csv` of https://github.com/PokeAPI/pokeapi
+    pokeapi: PathBuf,
+    #[arg(short, long, default_value = "data/learnsets.csv")]
+    output: PathBuf,
+}
+
+const ENGLISH: &str = "9";
+
+fn read(dir: &Path, table: &str) -> csv::Result<Vec<csv::StringRecord>> {
+    csv::Reader::from_path(dir.join(format!("{table}.csv")))?
+        .into_records()
+        .collect()
+}
+
+/// Learnsets of every species and form of the data files in PokeAPI's tables, one per generation.
+/// Level up moves learnt at different levels across the generation's games keep the lowest
+fn learnables(dir: &Path) -> csv::Result<Vec<Learnable>> {
+    // version_groups: id,identifier,generation_id,order
+    let generations: HashMap<String, u8> = read(dir, "version_groups")?
+        .iter()
+        .map(|r| (r[0].to_string(), r[2].parse().unwrap()))
+        .collect();
+    // pokemon_form_names: pokemon_form_id,local_language_id,form_name,pokemon_name
+    let form_names: HashMap<String, String> = read(dir, "pokemon_form_names")?
+        .iter()
+        .filter(|r| &r[1] == ENGLISH && !r[3].is_empty())
+        .map(|r| (r[0].to_string(), r[3].to_string()))
+        .collect();
+    // pokemon_forms: id,identifier,form_identifier,pokemon_id,introduced_in_version_group_id,is_default,...
+    // Names like "Heat Rotom" of the default form of each Pokemon
+    let pokemon_names: HashMap<String, &String> = read(dir, "pokemon_forms")?
+        .iter()
+        .filter(|r| &r[5] == "1")
+        .filter_map(|r| Some((r[3].to_string(), form_names.get(&r[0])?)))
+        .collect();
+    // pokemon: id,identifier,species_id,height,weight,base_experience,order,is_default
+    let species = Species::all(Generation::Gen6Plus);
+    let pokemon: HashMap<String, &Species> = read(dir, "pokemon")?
+        .iter()
+        .filter_map(|r| {
+            let dex: u16 = r[2].parse().unwrap();
+            let form = species.iter().filter(|s| s.dex == dex).find(|s| {
+                if &r[7] == "1" {
+                    s.form == 0
+                } else {
+                    s.form_name.as_ref() == pokemon_names.get(&r[0]).copied()
+                }
+            })?;
+            Some((r[0].to_string(), form.as_ref()))
+        })
+        .collect();
+    let moves: HashMap<String, String> = Move::all()
+        .into_iter()
+        .map(|m| (m.id.to_string(), m.name))
+        .collect();
+    // pokemon_moves: pokemon_id,version_group_id,move_id,pokemon_move_method_id,level,order
+    let learnables = read(dir, "pokemon_moves")?
+        .iter()
+        .filter_map(|r| {
+            let species = pokemon.get(&r[0])?;
+            // pokemon_move_methods, skipping the event and spin-off game ones
+            let method = match &r[3] {
+                "1" => LearnMethod::LevelUp(r[4].parse().unwrap()),
+                "2" => LearnMethod::Egg,
+                "3" => LearnMethod::Tutor,
+                "4" => LearnMethod::Machine,
+                _ => return None,
+            };
+            Some(Learnable {
+                species: species.name.clone(),
+                form: species.form,
+                move_name: moves.get(&r[2])?.clone(),
+                method,
+                generation: generations[&r[1]],
+            })
+        })
+        .sorted()
+        .unique_by(|l| {
+            let method = match l.method {
+                LearnMethod::LevelUp(_) => LearnMethod::LevelUp(0),
+                method => method,
+            };
+            (
+                l.species.clone(),
+                l.form,
+                l.move_name.clone(),
+                method,
+                l.generation,
+            )
+        })
+        .sorted_by(|a, b| {
+            (&a.species, a.form, a.generation, a.method).cmp(&(
+                &b.species,
+                b.form,
+                b.generation,
+                b.method,
+            ))
+        })
+        .collect();
+    Ok(learnables)
+}
+
+/// Converts PokeAPI's CSV tables into the learnsets file
+fn main() -> csv::Result<()> {
+    let cli = Cli::parse();
+    let learnables = learnables(&cli.pokeapi)?;
+    let mut writer = csv::Writer::from_path(&cli.output)?;
+    writer.write_record(["species", "form", "move", "method", "level", "generation"])?;
+    for learnable in &learnables {
+        writer.write_record(&learnable.to_record())?;
+    }
+    writer.flush()?;
+    println!(
+        "Wrote {} moves of {} forms to {}",
+        learnables.len(),
+        learnables
+            .iter()
+            .map(|l| (&l.species, l.form))
+            .unique()
+            .count(),
+        cli.output.display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_learnables() {
+        let dir = std::env::temp_dir().join("type-theory-pokeapi");
+        fs::create_dir_all(&dir).unwrap();
+        let tables = [
+            (
+                "version_groups",
+                "id,identifier,generation_id,order\n17,sword-shield,8,20\n25,scarlet-violet,9,27\n",
+            ),
+            (
+                "pokemon",
+                "id,identifier,species_id,height,weight,base_experience,order,is_default\n\
+                 445,garchomp,445,19,950,300,563,1\n\
+                 479,rotom,479,3,3,154,603,1\n\
+                 10008,rotom-heat,479,3,3,182,604,0\n",
+            ),
+            (
+                "pokemon_forms",
+                "id,identifier,form_identifier,pokemon_id,introduced_in_version_group_id,is_default,is_battle_only,is_mega,form_order,order\n\
+                 445,garchomp,,445,8,1,0,0,1,600\n\
+                 479,rotom,,479,8,1,0,0,1,620\n\
+                 10059,rotom-heat,heat,10008,9,1,0,0,2,621\n",
+            ),
+            (
+                "pokemon_form_names",
+                "pokemon_form_id,local_language_id,form_name,pokemon_name\n\
+                 10059,9,Heat Rotom,Heat Rotom\n\
+                 10059,5,Forme Chaleur,Motisma Chaleur\n",
+            ),
+            (
+                "pokemon_moves",
+                "pokemon_id,version_group_id,move_id,pokemon_move_method_id,level,order\n\
+                 445,17,89,1,1,\n\
+                 445,17,89,4,0,\n\
+                 445,25,89,1,40,\n\
+                 445,25,89,1,1,\n\
+                 445,25,200,2,0,\n\
+                 445,25,89,10,0,\n\
+                 10008,25,315,10,0,\n\
+                 10008,25,85,4,0,\n\
+                 445,25,99999,4,0,\n",
+            ),
+        ];
+        for (table, contents) in tables {
+            fs::write(dir.join(format!("{table}.csv")), contents).unwrap();
+        }
+        let learnable = |species: &str, form, move_name: &str, method, generation| Learnable {
+            species: species.to_string(),
+            form,
+            move_name: move_name.to_string(),
+            method,
+            generation,
+        };
+        assert_eq!(
+            learnables(&dir).unwrap(),
+            vec![
+                learnable("Garchomp", 0, "Earthquake", LearnMethod::LevelUp(1), 8),
+                learnable("Garchomp", 0, "Earthquake", LearnMethod::Machine, 8),
+                // The lowest level of the generation, without form change moves or unknown moves
+                learnable("Garchomp", 0, "Earthquake", LearnMethod::LevelUp(1), 9),
+                learnable("Garchomp", 0, "Outrage", LearnMethod::Egg, 9),
+                learnable("Rotom", 1, "Thunderbolt", LearnMethod::Machine, 9),
+            ]
+        );
+    }
+}
@@ -1,16 +1,19 @@
+use itertools::Itertools;
 use type_theory::{
     analysis::{
         checks::{self},
-        offensive_coverage::{offensive_coverage, offensive_coverage_impl},
+        coverage_moves::suggest_coverage_moves,
+        offensive_coverage::{move_coverage_impl, walled},
         score,
+        speed::{outspeeding_threats, speed_tiers},
     },
     injest::parse_names,
     pokemon::Pokemon,
+    ruleset::{Generation, Ruleset},
 };
 
 /// Given a team, finds appropriate checks for an opposing Pokemon
 fn main() {
-    let team: Vec<Pokemon> = serde_json::from_str(r#"[{"species":"Comfey","typing":["Fairy"],"ability":null,"moves":[]},{"species":"Excadrill","typing":["Ground","Steel"],"ability":null,"moves":[]},{"species":"Houndoom","typing":["Fire","Dark"],"ability":null,"moves":[]},{"species":"Pansage","typing":["Grass"],"ability":null,"moves":[]},{"species":"Slowpoke","typing":["Water","Psychic"],"ability":null,"moves":[]},{"species":"Zubat","typing":["Poison","Flying"],"ability":null,"moves":[]}]"#).unwrap();
     let team: Vec<Pokemon> = parse_names(vec![
         "Comfey",
         "Excadrill",
@@ -18,19 +21,43 @@ fn main() {
         "Pansage",
         "Slowpoke",
         "Zubat",
-    ])
+    ], Generation::default())
     .collect();
 
-    let score = score::<3>(&team);
+    let rules = Ruleset::default();
+    let score = score::<4>(&team, &rules);
     println!("Score: {:?}", score);
 
-    let unchecked_checks = checks::counter_balance(&team);
+    let unchecked_checks = checks::counter_balance(&team, &rules);
     println!("Unchecked checks: {:?}", unchecked_checks);
 
-    let missing_coverage = offensive_coverage_impl(&team)
+    let missing_coverage = move_coverage_impl(&team, &rules)
         .into_iter()
-        .filter(|(p, s)| *s == 0)
+        .filter(|(_, s)| *s == 0)
         .map(|(p, _)| p)
         .collect::<Vec<_>>();
     println!("Missing coverage: {:?}", missing_coverage);
+    println!("Walled: {:?}", walled(&team, &rules));
+
+    for (p, suggestions) in suggest_coverage_moves(&team, &rules).into_iter().unique_by(|(p, _)| p.name()) {
+        for suggestion in suggestions {
+            let fixes = suggestion.fixes.iter().map(|f| &f.typing).unique().collect::<Vec<_>>();
+            println!("{}: {} fixes {:?}", p.name(), suggestion.move_.name, fixes);
+        }
+    }
+
+    for (speed, tier) in speed_tiers(&team) {
+        let species = tier.iter().map(|p| &p.species).unique().collect::<Vec<_>>();
+        println!("Speed {speed}: {species:?}");
+    }
+    let threats = Pokemon::all(rules.generation())
+        .iter()
+        .unique_by(|p| &p.species)
+        .cloned()
+        .collect::<Vec<_>>();
+    let outspeeding = outspeeding_threats(&team, &threats)
+        .into_iter()
+        .map(|p| &p.species)
+        .collect::<Vec<_>>();
+    println!("Outspeed every member: {:?}", outspeeding);
 }
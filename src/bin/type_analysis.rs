@@ -1,7 +1,6 @@
 use std::collections::{BTreeMap, BTreeSet};
 use itertools::Itertools;
-use strum::IntoEnumIterator;
-use type_theory::{injest::parse_pkhex_dump, pokemon::Pokemon, typing::{BasicType, TypeTrait}};
+use type_theory::{pokemon::Pokemon, ruleset::Generation, typing::{AttackTrait, BasicType, Multiplier}};
 
 fn main() {
     // // Find all pokemon with zero or one weakness
@@ -30,15 +29,15 @@ fn main() {
     // Find all pokemon hit not hit super effectively by 4 types
     const NUM_TYPES: usize = 6;
     let mut mappings: BTreeMap<[BasicType; NUM_TYPES], BTreeSet<Pokemon>> = BTreeMap::new();
-    for types in BasicType::iter().combinations(NUM_TYPES) {
+    for types in Generation::default().chart().types().combinations(NUM_TYPES) {
         if types.contains(&BasicType::Normal) {
             continue;
         }
         let types: Vec<BasicType> = types;
-        let not_super_effective: BTreeSet<Pokemon> = Pokemon::all_unique_type_chart()
+        let not_super_effective: BTreeSet<Pokemon> = Pokemon::all_unique_type_chart(Generation::default())
             .iter()
             .filter(|poke| {
-                types.iter().all(|t| poke.defense().get(*t) <= 1.0)
+                types.iter().all(|t| t.effectiveness(*poke) <= Multiplier::NEUTRAL)
             })
             .cloned()
             .collect();
@@ -0,0 +1,362 @@
+use crate::{
+    pokemon::{DamageClass, Move, Pokemon},
+    ruleset::{Profile, Ruleset},
+    stats::{Individual, Nature, Stats},
+    typing::{Attack, AttackTrait, BasicType, Multiplier, SpecialMove, TypeTrait},
+};
+
+/// Level assumed for Pokemon without a known individual
+pub const DEFAULT_LEVEL: u8 = 50;
+
+/// Possible damage of one hit, ignoring critical hits
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Damage {
+    /// One roll per random factor from 85% to 100%, in increasing order
+    pub rolls: Vec<u32>,
+    /// HP of the target
+    pub hp: u32,
+}
+
+impl Damage {
+    pub fn min(&self) -> u32 {
+        self.rolls[0]
+    }
+
+    pub fn max(&self) -> u32 {
+        self.rolls[self.rolls.len() - 1]
+    }
+
+    /// Lowest roll as a percentage of the target's HP
+    pub fn min_percent(&self) -> f64 {
+        100.0 * self.min() as f64 / self.hp as f64
+    }
+
+    /// Highest roll as a percentage of the target's HP
+    pub fn max_percent(&self) -> f64 {
+        100.0 * self.max() as f64 / self.hp as f64
+    }
+
+    /// Chance that `hits` hits KO the target from full HP
+    pub fn ko_chance(&self, hits: usize) -> f64 {
+        let hp = self.hp as usize;
+        // Chance of each total damage dealt, capped at the target's HP
+        let mut dealt = vec![0.0; hp + 1];
+        dealt[0] = 1.0;
+        for _ in 0..hits {
+            let mut next = vec![0.0; hp + 1];
+            for (total, chance) in dealt.iter().enumerate().filter(|(_, c)| **c > 0.0) {
+                for roll in &self.rolls {
+                    next[(total + *roll as usize).min(hp)] += chance / self.rolls.len() as f64;
+                }
+            }
+            dealt = next;
+        }
+        dealt[hp]
+    }
+
+    /// Fewest hits that can KO, `None` if the move deals no damage
+    pub fn hits_to_ko(&self) -> Option<u32> {
+        match self.max() {
+            0 => None,
+            max => Some(self.hp.div_ceil(max)),
+        }
+    }
+}
+
+/// Stats in battle, or those of a neutral natured level 50 with perfect IVs and no EVs
+/// when the individual is unknown. `None` without species data
+pub fn battle_stats(pokemon: &Pokemon) -> Option<Stats> {
+    pokemon.stats().or_else(|| {
+        let base = &pokemon.species_data.as_ref()?.base_stats;
+        let ivs = Stats::from_fn(|_| 31);
+        let individual = Individual::new(DEFAULT_LEVEL, Nature::Hardy, ivs, Stats::default(), base);
+        Some(individual.stats)
+    })
+}
+
+/// Damage class of the attacker's better attacking stat, physical on ties or without species data
+pub fn attacking_class(attacker: &Pokemon) -> DamageClass {
+    match battle_stats(attacker) {
+        Some(stats) if stats.special_attack > stats.attack => DamageClass::Special,
+        _ => DamageClass::Physical,
+    }
+}
+
+/// Stand-in for unknown moves: 80 power of type `t`, off the attacker's better attacking stat
+pub fn stab_move(attacker: &Pokemon, t: BasicType) -> Move {
+    let damage_class = attacking_class(attacker);
+    Move {
+        name: format!("{t:?} STAB"),
+        id: 0,
+        typing: t,
+        accuracy: Some(100),
+        power: Some(80),
+        pp: 15,
+        priority: 0,
+        generation: 1,
+        damage_class,
+        description: "".into(),
+    }
+}
+
+/// Damaging moves of the Pokemon, variable power ones like Low Kick included, or a `stab_move`
+/// per attacking type when its moves are unknown
+pub fn damaging_moves(pokemon: &Pokemon) -> Vec<Move> {
+    if pokemon.moves.is_empty() {
+        pokemon
+            .attacking_types()
+            .into_iter()
+            .map(|t| stab_move(pokemon, t))
+            .collect()
+    } else {
+        pokemon
+            .moves
+            .iter()
+            .filter(|m| !m.is_status())
+            .cloned()
+            .collect()
+    }
+}
+
+/// Damage of the attacker's `damaging_moves` with the highest roll, `None` without any
+pub fn best_damage(
+    attacker: &Pokemon,
+    defender: &Pokemon,
+    rules: &Ruleset,
+) -> Option<(Move, Damage)> {
+    damaging_moves(attacker)
+        .into_iter()
+        .filter_map(|m| damage(attacker, &m, defender, rules).map(|d| (m, d)))
+        .max_by_key(|(_, d)| d.max())
+}
+
+/// Damage of `move_` from `attacker` against `defender`, with the physical/special split of
+/// gen 4 onwards. `None` for status moves, moves without a fixed power like Low Kick and
+/// Pokemon without species data
+pub fn damage(
+    attacker: &Pokemon,
+    move_: &Move,
+    defender: &Pokemon,
+    rules: &Ruleset,
+) -> Option<Damage> {
+    let attacker_stats = battle_stats(attacker)?;
+    let defender_stats = battle_stats(defender)?;
+    let (attack, defense) = match move_.damage_class {
+        DamageClass::Physical => (attacker_stats.attack, defender_stats.defense),
+        DamageClass::Special => (
+            attacker_stats.special_attack,
+            defender_stats.special_defense,
+        ),
+        DamageClass::Status => return None,
+    };
+    let level = attacker
+        .individual
+        .as_ref()
+        .map_or(DEFAULT_LEVEL, |i| i.level) as u32;
+    let hit = move_attack(attacker, move_);
+    let t = hit.typing;
+
+    let power = move_.power?;
+    let power = match attacker.ability {
+        Some(ability) => ability.power_multiplier(move_.typing).apply(power),
+        None => power,
+    };
+    let power = match attacker.item {
+        Some(item) => item.power_multiplier(t).apply(power),
+        None => power,
+    };
+    let power = rules
+        .field()
+        .terrain_multiplier(t, attacker.is_grounded())
+        .apply(power);
+    let base = (2 * level / 5 + 2) * power * attack as u32 / defense.max(1) as u32 / 50 + 2;
+    // Weather comes before the random roll, STAB and effectiveness
+    let field = rules.field().damage_multiplier(t, defender.is_grounded());
+    let base = field.apply(base);
+
+    let stab = if attacker.stabs().contains(&t) {
+        attacker
+            .ability
+            .map_or(Multiplier::new(3, 2), |a| a.stab_bonus())
+    } else {
+        Multiplier::NEUTRAL
+    };
+    let effectiveness =
+        attack_effectiveness(&hit, move_.special(), &rules.profile(defender), rules);
+    let rolls = (85..=100)
+        .map(|random| {
+            let damage = effectiveness.apply(stab.apply(base * random / 100));
+            if effectiveness.is_immune() || field.is_immune() {
+                0
+            } else {
+                damage.max(1)
+            }
+        })
+        .collect();
+    Some(Damage {
+        rolls,
+        hp: defender_stats.hp as u32,
+    })
+}
+
+/// Attack `move_` makes when used by `attacker`, after its ability changes the move's type
+pub fn move_attack(attacker: &Pokemon, move_: &Move) -> Attack {
+    Attack {
+        typing: attacker
+            .ability
+            .map_or(move_.typing, |a| a.attack_type(move_.typing)),
+        ability: attacker.attacker_ability(),
+        item: attacker.attacker_item(),
+    }
+}
+
+/// Multiplier of an attack against a profiled defender, honouring moves like Freeze-Dry
+pub fn attack_effectiveness(
+    attack: &Attack,
+    special: Option<SpecialMove>,
+    defender: &Profile,
+    rules: &Ruleset,
+) -> Multiplier {
+    match special {
+        Some(special) => {
+            let t = attack.typing;
+            let m = special.effectiveness_with(t, defender.pokemon, rules.field_chart());
+            let m = attack.ability.map_or(m, |a| a.modify_effectiveness(m));
+            attack.item.map_or(m, |i| i.modify_effectiveness(m))
+        }
+        None => defender.taken_from(attack),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        field::{Field, Weather},
+        fixtures::{at, move_, pokemon},
+        item::Item,
+        typing::{Ability, BasicType::*},
+    };
+    use std::str::FromStr;
+
+    #[test]
+    fn test_damage() {
+        let rules = &Ruleset::default();
+        // 31 IV, 0 EV, neutral natured level 50s
+        let garchomp = at(&pokemon("Garchomp"), 50, Nature::Hardy);
+        let heatran = at(&pokemon("Heatran"), 50, Nature::Hardy);
+        let earthquake = damage(&garchomp, &move_("Earthquake"), &heatran, rules).unwrap();
+        assert_eq!(earthquake.hp, 166);
+        assert_eq!(earthquake.rolls.len(), 16);
+        assert_eq!((earthquake.min(), earthquake.max()), (268, 324));
+        assert_eq!(earthquake.hits_to_ko(), Some(1));
+        assert_eq!(earthquake.ko_chance(1), 1.0);
+
+        let none = damage(
+            &garchomp,
+            &move_("Earthquake"),
+            &at(&pokemon("Skarmory"), 50, Nature::Hardy),
+            rules,
+        )
+        .unwrap();
+        assert_eq!(none.max(), 0);
+        assert_eq!(none.hits_to_ko(), None);
+        assert_eq!(none.ko_chance(3), 0.0);
+        assert!(damage(&garchomp, &move_("Swords Dance"), &heatran, rules).is_none());
+        // Variable power moves deal typed damage, but their damage is not calculated
+        let low_kick = Pokemon {
+            moves: vec![move_("Low Kick"), move_("Swords Dance")],
+            ..garchomp.clone()
+        };
+        assert_eq!(damaging_moves(&low_kick), vec![move_("Low Kick")]);
+        assert!(damage(&low_kick, &move_("Low Kick"), &heatran, rules).is_none());
+        assert!(damage(
+            &Pokemon::from(Ground),
+            &move_("Earthquake"),
+            &heatran,
+            rules
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_ko_chance() {
+        let damage = Damage {
+            rolls: (85..=100).collect(),
+            hp: 100,
+        };
+        assert_eq!(damage.ko_chance(1), 1.0 / 16.0);
+        assert_eq!(damage.ko_chance(2), 1.0);
+        assert_eq!(damage.hits_to_ko(), Some(1));
+        assert_eq!(damage.min_percent(), 85.0);
+    }
+
+    #[test]
+    fn test_modifiers() {
+        let rules = &Ruleset::default();
+        let blissey = at(&pokemon("Blissey"), 50, Nature::Hardy);
+        let sylveon = at(&pokemon("Sylveon"), 50, Nature::Hardy);
+        let hyper_voice = move_("Hyper Voice");
+        let plain = damage(&sylveon, &hyper_voice, &blissey, rules).unwrap();
+        let pixilate = Pokemon {
+            ability: Some(Ability::Pixilate),
+            ..sylveon.clone()
+        };
+        // Fairy STAB after Pixilate, with its 1.2x boost
+        let pixilated = damage(&pixilate, &hyper_voice, &blissey, rules).unwrap();
+        assert!(pixilated.max() > plain.max());
+        let fairy_voice = Move {
+            typing: Fairy,
+            ..hyper_voice.clone()
+        };
+        let unboosted = damage(&pixilate, &fairy_voice, &blissey, rules).unwrap();
+        assert!(pixilated.max() > unboosted.max());
+        assert_eq!(
+            Ability::Pixilate.power_multiplier(Fairy),
+            Multiplier::NEUTRAL
+        );
+        let plate = Pokemon {
+            item: Some(Item::from_str("Pixie Plate").unwrap()),
+            ..pixilate.clone()
+        };
+        assert!(damage(&plate, &hyper_voice, &blissey, rules).unwrap().max() > pixilated.max());
+        let stand_in = stab_move(&sylveon, Fairy);
+        assert_eq!(stand_in.damage_class, DamageClass::Special);
+        assert_eq!(stand_in.power, Some(80));
+    }
+
+    #[test]
+    fn test_weather() {
+        let rain = Ruleset::default().with_field(Field {
+            weather: Some(Weather::Rain),
+            ..Field::default()
+        });
+        let blissey = at(&pokemon("Blissey"), 50, Nature::Hardy);
+        let tinted = Pokemon {
+            ability: Some(Ability::TintedLens),
+            ..at(&pokemon("Heatran"), 50, Nature::Hardy)
+        };
+        // A neutral hit in rain is still neutral, Tinted Lens only doubles resisted hits
+        let effectiveness = |p: &Pokemon, name: &str, rules: &Ruleset| {
+            let attack = move_attack(p, &move_(name));
+            attack_effectiveness(&attack, None, &rules.profile(&blissey), rules)
+        };
+        assert_eq!(effectiveness(&tinted, "Surf", &rain), Multiplier::NEUTRAL);
+        assert_eq!(
+            effectiveness(&tinted, "Flamethrower", &rain),
+            Multiplier::NEUTRAL
+        );
+        // The damage still changes
+        let dry = &Ruleset::default();
+        let surf = |rules: &Ruleset| damage(&tinted, &move_("Surf"), &blissey, rules).unwrap();
+        let flamethrower =
+            |rules: &Ruleset| damage(&tinted, &move_("Flamethrower"), &blissey, rules).unwrap();
+        assert!(surf(&rain).max() > surf(dry).max());
+        assert!(flamethrower(&rain).max() < flamethrower(dry).max());
+        let heavy_rain = Ruleset::default().with_field(Field {
+            weather: Some(Weather::HeavyRain),
+            ..Field::default()
+        });
+        assert_eq!(flamethrower(&heavy_rain).max(), 0);
+    }
+}
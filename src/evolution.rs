@@ -0,0 +1,224 @@
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, OnceLock};
+use strum::EnumCount;
+
+use crate::{pokemon::Pokemon, ruleset::Generation, species::Species};
+
+/// What triggers an evolution
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum EvolutionMethod {
+    /// Reaching the level, sometimes with a further condition like the time of day
+    Level(u8),
+    /// Using or holding the item, e.g. "Thunder Stone"
+    Item(String),
+    Friendship,
+    Trade,
+    /// Moves, locations, stats of a battle and so on
+    Other,
+}
+
+/// One step of an evolution line, between forms of two species
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Evolution {
+    pub from: String,
+    pub from_form: u8,
+    pub to: String,
+    pub to_form: u8,
+    pub method: EvolutionMethod,
+}
+
+impl Evolution {
+    /// Evolutions between species and forms in the games of the generation
+    pub fn all(generation: Generation) -> &'static Vec<Evolution> {
+        static CELLS: [OnceLock<Vec<Evolution>>; Generation::COUNT] =
+            [const { OnceLock::new() }; Generation::COUNT];
+        CELLS[generation as usize].get_or_init(|| {
+            csv::Reader::from_path("data/evolutions.csv")
+                .unwrap()
+                .into_records()
+                .map(|r| Evolution::from_record(&r.unwrap()))
+                .filter(|e| {
+                    Species::get(generation, &e.from, e.from_form).is_some()
+                        && Species::get(generation, &e.to, e.to_form).is_some()
+                })
+                .collect()
+        })
+    }
+
+    /// Parses a row of `evolutions.csv`
+    pub fn from_record(record: &csv::StringRecord) -> Evolution {
+        // from,from_form,to,to_form,method,level,item
+        let field = |i: usize| record.get(i).unwrap_or_default().trim();
+        let number = |i: usize| {
+            field(i)
+                .parse()
+                .unwrap_or_else(|_| panic!("Invalid number {:?} for {:?}", field(i), field(0)))
+        };
+        let method = match field(4) {
+            "level" => EvolutionMethod::Level(number(5)),
+            "item" => EvolutionMethod::Item(field(6).to_string()),
+            "friendship" => EvolutionMethod::Friendship,
+            "trade" => EvolutionMethod::Trade,
+            "other" => EvolutionMethod::Other,
+            method => panic!("Unknown evolution method {method:?}"),
+        };
+        Evolution {
+            from: field(0).to_string(),
+            from_form: number(1),
+            to: field(2).to_string(),
+            to_form: number(3),
+            method,
+        }
+    }
+
+    /// Evolutions of the species' form
+    pub fn of(species: &Species, generation: Generation) -> Vec<&'static Evolution> {
+        Evolution::all(generation)
+            .iter()
+            .filter(|e| e.from == species.name && e.from_form == species.form)
+            .collect()
+    }
+
+    /// Whether it can happen by the level cap, with evolution items only if `items`.
+    /// Friendship can be raised at any level, trades and other methods are never counted on
+    pub fn available_by(&self, level_cap: u8, items: bool) -> bool {
+        match self.method {
+            EvolutionMethod::Level(level) => level <= level_cap,
+            EvolutionMethod::Item(_) => items,
+            EvolutionMethod::Friendship => true,
+            EvolutionMethod::Trade | EvolutionMethod::Other => false,
+        }
+    }
+
+    /// Species' form it evolves into
+    pub fn target(&self, generation: Generation) -> &'static Arc<Species> {
+        Species::get(generation, &self.to, self.to_form).unwrap()
+    }
+}
+
+/// Every later stage the species can reach by the level cap, in evolution order
+pub fn evolutions_by(
+    species: &Species,
+    generation: Generation,
+    level_cap: u8,
+    items: bool,
+) -> Vec<&'static Arc<Species>> {
+    let mut reached = vec![];
+    let mut stages = vec![species];
+    while let Some(stage) = stages.pop() {
+        for evolution in Evolution::of(stage, generation) {
+            if evolution.available_by(level_cap, items) {
+                let target = evolution.target(generation);
+                reached.push(target);
+                stages.push(target);
+            }
+        }
+    }
+    reached
+}
+
+/// The pool with everything its members can evolve into by the level cap, evolved Pokemon taking
+/// the typing and abilities of their new species. Later stages come first, so that filters like
+/// `unique_by_type_ability` keep the most evolved Pokemon
+pub fn expand_pool(
+    pool: &[Pokemon],
+    generation: Generation,
+    level_cap: u8,
+    items: bool,
+) -> Vec<Pokemon> {
+    pool.iter()
+        .flat_map(|p| {
+            let mut line = match &p.species_data {
+                Some(species) => evolutions_by(species, generation, level_cap, items)
+                    .into_iter()
+                    .rev()
+                    .flat_map(|s| Pokemon::of_species(s, generation))
+                    .collect(),
+                None => vec![],
+            };
+            line.push(p.clone());
+            line
+        })
+        .unique()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{injest::parse_names, pokemon::Typing, typing::BasicType::*};
+
+    fn species(name: &str) -> &'static Arc<Species> {
+        Species::get(Generation::Gen6Plus, name, 0).unwrap()
+    }
+
+    fn names(species: Vec<&Arc<Species>>) -> Vec<&str> {
+        species.iter().map(|s| s.display_name()).collect()
+    }
+
+    #[test]
+    fn test_evolutions() {
+        let generation = Generation::Gen6Plus;
+        let charmander = species("Charmander");
+        assert_eq!(
+            Evolution::of(charmander, generation)[0].method,
+            EvolutionMethod::Level(16)
+        );
+        assert!(names(evolutions_by(charmander, generation, 15, true)).is_empty());
+        assert_eq!(
+            names(evolutions_by(charmander, generation, 35, true)),
+            vec!["Charmeleon"]
+        );
+        assert_eq!(
+            names(evolutions_by(charmander, generation, 100, true)),
+            vec!["Charmeleon", "Charizard"]
+        );
+        // Stones only with items allowed, trades never
+        let eevee = species("Eevee");
+        assert_eq!(
+            names(evolutions_by(eevee, generation, 5, false)),
+            vec!["Espeon", "Umbreon", "Sylveon"]
+        );
+        assert_eq!(evolutions_by(eevee, generation, 5, true).len(), 8);
+        assert_eq!(
+            names(evolutions_by(species("Machop"), generation, 100, true)),
+            vec!["Machoke"]
+        );
+        // Regional forms evolve into their own forms
+        let vulpix = Species::get(generation, "Vulpix", 1).unwrap();
+        assert_eq!(
+            names(evolutions_by(vulpix, generation, 1, true)),
+            vec!["Alolan Ninetales"]
+        );
+        // Evolutions into later species are left out
+        assert!(Evolution::of(species("Eevee"), Generation::Gen2To5)
+            .iter()
+            .all(|e| e.to != "Sylveon"));
+        assert!(Evolution::all(Generation::Gen1)
+            .iter()
+            .all(|e| e.to != "Crobat"));
+    }
+
+    #[test]
+    fn test_expand_pool() {
+        let generation = Generation::Gen6Plus;
+        let pool = parse_names(vec!["Scraggy", "Azurill"], generation).collect::<Vec<_>>();
+        let expanded = expand_pool(&pool, generation, 40, false);
+        assert_eq!(expanded[0].species, "Scrafty");
+        // Scrafty keeps Scraggy's typing
+        let scrafty = expanded.iter().find(|p| p.species == "Scrafty").unwrap();
+        assert_eq!(scrafty.typing, Typing::from((Dark, Fighting)));
+        assert!(expand_pool(&pool, generation, 38, false)
+            .iter()
+            .all(|p| p.species != "Scrafty"));
+        // Normal/Fairy Azurill becomes a Water/Fairy type
+        let azumarill = expanded.iter().find(|p| p.species == "Azumarill").unwrap();
+        assert_eq!(azumarill.typing, Typing::from((Water, Fairy)));
+        assert!(expanded.iter().any(|p| p.species == "Marill"));
+        assert!(expanded.iter().any(|p| p.species == "Azurill"));
+        let gen5 = expand_pool(&pool, Generation::Gen2To5, 40, false);
+        let azumarill = gen5.iter().find(|p| p.species == "Azumarill").unwrap();
+        assert_eq!(azumarill.typing, Typing::from(Water));
+    }
+}
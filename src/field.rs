@@ -0,0 +1,225 @@
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter, EnumString};
+
+use crate::typing::{BasicType, Multiplier, Relationship, TypeChart};
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    EnumIter,
+    Ord,
+    PartialOrd,
+    EnumString,
+    Display,
+    Serialize,
+    Deserialize,
+)]
+#[strum(serialize_all = "kebab-case")]
+pub enum Weather {
+    /// Water hits 1.5x, Fire 0.5x
+    Rain,
+    /// Fire hits 1.5x, Water 0.5x
+    Sun,
+    /// Primordial Sea: Fire moves fail
+    HeavyRain,
+    /// Desolate Land: Water moves fail
+    HarshSun,
+    Sandstorm,
+    Snow,
+    /// Delta Stream: Flying types lose their weaknesses
+    StrongWinds,
+}
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    EnumIter,
+    Ord,
+    PartialOrd,
+    EnumString,
+    Display,
+    Serialize,
+    Deserialize,
+)]
+#[strum(serialize_all = "kebab-case")]
+pub enum Terrain {
+    Electric,
+    Grassy,
+    Psychic,
+    /// Dragon hits grounded Pokemon 0.5x
+    Misty,
+}
+
+/// Field conditions that change how hard types hit
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Field {
+    pub weather: Option<Weather>,
+    pub terrain: Option<Terrain>,
+    /// Grounds every Pokemon
+    pub gravity: bool,
+}
+
+impl Field {
+    /// Chart with the per-type changes of this field, e.g. Flying losing its weaknesses in Strong Winds
+    pub fn chart(&self, chart: &TypeChart) -> TypeChart {
+        let chart = if self.gravity {
+            chart.grounded()
+        } else {
+            chart.clone()
+        };
+        match self.weather {
+            Some(Weather::StrongWinds) => chart.map_type(BasicType::Flying, |_, m| {
+                if m > Multiplier::NEUTRAL {
+                    Multiplier::NEUTRAL
+                } else {
+                    m
+                }
+            }),
+            _ => chart,
+        }
+    }
+
+    /// Damage taken by type: the effectiveness `defense` computed under `chart` times the
+    /// `damage_multiplier` of the field. Not an effectiveness, a neutral Water hit in rain stays
+    /// neutral for Expert Belt and the like
+    pub fn damage_taken(&self, defense: Relationship, grounded: bool) -> Relationship {
+        defense.map(|t, m| m * self.damage_multiplier(t, grounded))
+    }
+
+    /// Damage multiplier weather and Misty Terrain give hits of type `t`, applied apart from
+    /// type effectiveness
+    pub fn damage_multiplier(&self, t: BasicType, defender_grounded: bool) -> Multiplier {
+        let m = self.weather_multiplier(t);
+        match (self.terrain, t) {
+            (Some(Terrain::Misty), BasicType::Dragon) if defender_grounded || self.gravity => {
+                m * Multiplier::RESISTED
+            }
+            _ => m,
+        }
+    }
+
+    /// Damage multiplier the weather gives moves of type `t`
+    pub fn weather_multiplier(&self, t: BasicType) -> Multiplier {
+        use BasicType::*;
+        match (self.weather, t) {
+            (Some(Weather::Rain | Weather::HeavyRain), Water)
+            | (Some(Weather::Sun | Weather::HarshSun), Fire) => Multiplier::new(3, 2),
+            (Some(Weather::Rain), Fire) | (Some(Weather::Sun), Water) => Multiplier::RESISTED,
+            (Some(Weather::HeavyRain), Fire) | (Some(Weather::HarshSun), Water) => {
+                Multiplier::IMMUNE
+            }
+            _ => Multiplier::NEUTRAL,
+        }
+    }
+
+    /// Damage multiplier terrain gives a grounded attacker's moves of type `t`.
+    /// Left out of `damage_multiplier` since it depends on the attacker
+    pub fn terrain_multiplier(&self, t: BasicType, attacker_grounded: bool) -> Multiplier {
+        if !(attacker_grounded || self.gravity) {
+            return Multiplier::NEUTRAL;
+        }
+        match (self.terrain, t) {
+            (Some(Terrain::Electric), BasicType::Electric)
+            | (Some(Terrain::Grassy), BasicType::Grass)
+            | (Some(Terrain::Psychic), BasicType::Psychic) => Multiplier::new(13, 10),
+            _ => Multiplier::NEUTRAL,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        pokemon::Pokemon,
+        typing::{Ability, BasicType::*, TypeTrait},
+    };
+
+    fn field(weather: Option<Weather>, terrain: Option<Terrain>, gravity: bool) -> Field {
+        Field {
+            weather,
+            terrain,
+            gravity,
+        }
+    }
+
+    #[test]
+    fn test_weather() {
+        let rain = field(Some(Weather::Rain), None, false);
+        let ferrothorn = Pokemon::from((Grass, Steel)).defense_in(TypeChart::builtin(), &rain);
+        assert_eq!(ferrothorn.get(Fire), 2.0);
+        assert_eq!(ferrothorn.get(Water), 0.75);
+        let harsh_sun = field(Some(Weather::HarshSun), None, false);
+        assert_eq!(
+            Fire.defense_in(TypeChart::builtin(), &harsh_sun).get(Water),
+            0.0
+        );
+        assert_eq!(
+            Grass.defense_in(TypeChart::builtin(), &harsh_sun).get(Fire),
+            3.0
+        );
+    }
+
+    #[test]
+    fn test_strong_winds() {
+        let winds = field(Some(Weather::StrongWinds), None, false);
+        let rayquaza = Pokemon::from((Dragon, Flying)).defense_in(TypeChart::builtin(), &winds);
+        assert_eq!(rayquaza.get(Ice), 2.0);
+        assert_eq!(rayquaza.get(Rock), 1.0);
+        assert_eq!(rayquaza.get(Ground), 0.0);
+        assert_eq!(rayquaza.get(Grass), 0.25);
+    }
+
+    #[test]
+    fn test_gravity_and_terrain() {
+        let gravity = field(None, Some(Terrain::Misty), true);
+        let skarmory = Pokemon::from((Steel, Flying));
+        assert_eq!(
+            skarmory
+                .defense_in(TypeChart::builtin(), &gravity)
+                .get(Ground),
+            2.0
+        );
+        let levitate = Pokemon {
+            ability: Some(Ability::Levitate),
+            ..Pokemon::from(Ghost)
+        };
+        assert_eq!(
+            levitate
+                .defense_in(TypeChart::builtin(), &gravity)
+                .get(Ground),
+            1.0
+        );
+        // Misty Terrain only protects grounded Pokemon
+        let misty = field(None, Some(Terrain::Misty), false);
+        assert_eq!(
+            Pokemon::from(Dragon)
+                .defense_in(TypeChart::builtin(), &misty)
+                .get(Dragon),
+            1.0
+        );
+        assert_eq!(
+            skarmory
+                .defense_in(TypeChart::builtin(), &misty)
+                .get(Dragon),
+            0.5
+        );
+        assert_eq!(
+            skarmory
+                .defense_in(TypeChart::builtin(), &gravity)
+                .get(Dragon),
+            0.25
+        );
+        let electric = field(None, Some(Terrain::Electric), false);
+        assert_eq!(electric.terrain_multiplier(Electric, true), 1.3);
+        assert_eq!(electric.terrain_multiplier(Electric, false), 1.0);
+    }
+}
@@ -0,0 +1,40 @@
+//! Pokemon and moves shared by the unit tests
+
+use crate::{
+    injest::parse_names,
+    pokemon::{Move, Pokemon},
+    ruleset::Generation,
+    stats::{Individual, Nature, Stats},
+};
+
+/// The Pokemon of the name in the default generation
+pub fn pokemon(name: &str) -> Pokemon {
+    parse_names(vec![name], Generation::default())
+        .next()
+        .unwrap()
+}
+
+/// The Pokemon at the level and nature with 31 IVs and no EVs
+pub fn at(pokemon: &Pokemon, level: u8, nature: Nature) -> Pokemon {
+    let base = pokemon.species_data.as_ref().unwrap().base_stats;
+    Pokemon {
+        individual: Some(Individual::new(
+            level,
+            nature,
+            Stats::from_fn(|_| 31),
+            Stats::default(),
+            &base,
+        )),
+        ..pokemon.clone()
+    }
+}
+
+/// The move of the name
+pub fn move_(name: &str) -> Move {
+    Move::get(name).unwrap().clone()
+}
+
+/// The moves of the names
+pub fn moves(names: &[&str]) -> Vec<Move> {
+    names.iter().map(|name| move_(name)).collect()
+}
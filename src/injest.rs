@@ -1,16 +1,15 @@
 use crate::{
+    item::Item,
     pokemon::{Move, Pokemon, Typing},
-    typing::{combine_defense_charts, Ability, BasicType, Relationship, TypeTrait},
+    ruleset::Generation,
+    species::{FormKind, Species},
+    stats::{Individual, Nature, Stats},
+    typing::Ability,
 };
-use itertools::Itertools;
-use rand::seq::SliceRandom;
-use serde::{Deserialize, Serialize};
-use std::{collections::BTreeSet, path::Path, str::FromStr, sync::OnceLock};
-use strum::IntoEnumIterator;
+use std::{path::Path, str::FromStr, sync::Arc};
 
-pub fn parse_pkhex_dump(file: &str) -> Vec<Pokemon> {
+pub fn parse_pkhex_dump(file: &str, generation: Generation) -> Vec<Pokemon> {
     // "Position","Nickname","Species","Nature","Gender","ESV","HP_Type","Ability","Move1","Move2","Move3","Move4","HeldItem","HP","ATK","DEF","SPA","SPD","SPE","MetLoc","EggLoc","Ball","OT","Version","OTLang","Legal","EC","PID","IV_HP","IV_ATK","IV_DEF","IV_SPA","IV_SPD","IV_SPE","EXP","Level","EV_HP","EV_ATK","EV_DEF","EV_SPA","EV_SPD","EV_SPE","Cool","Beauty","Cute","Smart","Tough","Sheen","NotOT","AbilityNum","GenderFlag","Form","PokerusStrain","PokerusDays","MetLevel","OriginalTrainerGender","FatefulEncounter","IsEgg","IsNicknamed","IsShiny","TID16","SID16","TSV","Move1_PP","Move2_PP","Move3_PP","Move4_PP","Move1_PPUp","Move2_PPUp","Move3_PPUp","Move4_PPUp","Relearn1","Relearn2","Relearn3","Relearn4","Checksum","Friendship","EggYear","EggMonth","EggDay","MetYear","MetMonth","MetDay"
-    let all_pokemon = Pokemon::all();
     let all_moves = Move::all();
     let mut rdr = csv::Reader::from_path(file).unwrap();
     rdr.records()
@@ -18,38 +17,17 @@ pub fn parse_pkhex_dump(file: &str) -> Vec<Pokemon> {
             let record = r.unwrap();
             let species = record.get(2).unwrap().to_string();
             let ability = match record.get(7).unwrap() {
+                _ if !generation.has_abilities() => None,
                 "" => None,
-                a => match Ability::from_str(a) {
-                    Ok(a) => Some(a),
-                    Err(_) => None,
-                },
+                a => Ability::from_str(a).ok(),
             };
-            let typing = {
-                let matched_pokemon = all_pokemon.iter().find(|p| p.species == species).unwrap();
-                let form = record.get(51).unwrap();
-                if form != "0" {
-                    match matched_pokemon.species.as_str() {
-                        "Rotom" => match form {
-                            "0" => matched_pokemon.typing.clone(),
-                            "1" => Typing::from((BasicType::Electric, BasicType::Fire)),
-                            "2" => Typing::from((BasicType::Electric, BasicType::Water)),
-                            "3" => Typing::from((BasicType::Electric, BasicType::Ice)),
-                            "4" => Typing::from((BasicType::Electric, BasicType::Flying)),
-                            "5" => Typing::from((BasicType::Electric, BasicType::Grass)),
-                            _ => panic!("Invalid Rotom form"),
-                        },
-                        "Gastrodon" => matched_pokemon.typing.clone(),
-                        "Basculin" => match form {
-                            "0" => Typing::from(BasicType::Water),
-                            "1" => Typing::from(BasicType::Water),
-                            _ => panic!("Invalid Basculin form"),
-                        },
-                        _ => panic!("Unhandled form for {species:?} with form {form:?}"),
-                    }
-                } else {
-                    matched_pokemon.typing.clone()
-                }
+            let item = Item::from_str(record.get(12).unwrap()).ok();
+            let stats = |columns: [usize; 6]| {
+                Stats::from_fn(|stat| record.get(columns[stat as usize]).unwrap().parse().unwrap())
             };
+            let form = record.get(51).unwrap().parse().unwrap();
+            let species_data = species_form(&species, form, generation);
+            let typing = Typing::of(species_data, generation);
             let moves = vec![
                 record.get(8).unwrap(),
                 record.get(9).unwrap(),
@@ -71,28 +49,63 @@ pub fn parse_pkhex_dump(file: &str) -> Vec<Pokemon> {
                 }
             })
             .collect();
+            let computed = Individual::new(
+                record.get(35).unwrap().parse().unwrap(),
+                Nature::from_str(record.get(3).unwrap()).unwrap(),
+                stats([28, 29, 30, 31, 32, 33]),
+                stats([36, 37, 38, 39, 40, 41]),
+                &species_data.base_stats,
+            );
+            // The game's stats win, its base stats may differ from the data files
+            let actual = stats([13, 14, 15, 16, 17, 18]);
+            let individual = Individual {
+                mismatched: computed.stats.differences(&actual),
+                stats: actual,
+                ..computed
+            };
             Pokemon {
                 species,
                 typing,
                 ability,
                 moves,
+                tera: None,
+                item,
+                species_data: Some(species_data.clone()),
+                individual: Some(individual),
+                transformed: None,
             }
         })
         .collect()
 }
 
+/// Data of the species' form, or of its base form for forms without data of their own, which
+/// only change its looks like Unown's letters. A form whose stats differ shows up in
+/// `Individual::mismatched`
+fn species_form(species: &str, form: u8, generation: Generation) -> &'static Arc<Species> {
+    Species::get(generation, species, form)
+        .or_else(|| Species::get(generation, species, 0))
+        .unwrap_or_else(|| panic!("Could not find {species:?} in the list of all Pokemon"))
+}
+
 /// Parses a file of Pokemon names in each line
-pub fn parse_names_file(file: impl AsRef<Path>) -> Vec<Pokemon> {
-    parse_names(std::fs::read_to_string(file).unwrap().lines()).collect()
+pub fn parse_names_file(file: impl AsRef<Path>, generation: Generation) -> Vec<Pokemon> {
+    parse_names(std::fs::read_to_string(file).unwrap().lines(), generation).collect()
 }
 
-/// Parses a Pokemon name strings to a a list of Pokemon
-pub fn parse_names<'a, T: IntoIterator<Item = &'a str>>(names: T) -> impl Iterator<Item = Pokemon> + use <'a, T> {
-    let all_pokemon = Pokemon::all();
+/// Parses a Pokemon name strings to a a list of Pokemon, one per ability.
+/// A species name gives its base form and its alternate forms, e.g. "Rotom" gives every appliance.
+/// Single forms are named like "Heat Rotom", and regional forms only by name like "Alolan Vulpix"
+pub fn parse_names<'a, T: IntoIterator<Item = &'a str>>(names: T, generation: Generation) -> impl Iterator<Item = Pokemon> + use <'a, T> {
+    let all_pokemon = Pokemon::all(generation);
     names.into_iter().flat_map(move |name| {
         let species = name.to_string();
-        let matched_pokemon = all_pokemon.iter().filter(|p| p.species == species).collect::<Vec<_>>();
-        if matched_pokemon.len() == 0 {
+        // A form by its name like "Alolan Vulpix", or a species with its alternate forms
+        let alternate = |p: &Pokemon| p.species_data.as_ref().is_some_and(|s| s.form_kind == FormKind::Alternate);
+        let matched_pokemon = all_pokemon
+            .iter()
+            .filter(|p| p.name() == species || (p.species == species && alternate(p)))
+            .collect::<Vec<_>>();
+        if matched_pokemon.is_empty() {
             panic!("Could not find {species:?} in the list of all Pokemon");
         }
         matched_pokemon
@@ -102,10 +115,66 @@ pub fn parse_names<'a, T: IntoIterator<Item = &'a str>>(names: T) -> impl Iterat
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{stats::Stat, typing::BasicType};
+    use itertools::Itertools;
+
     #[test]
     fn test_from_pkhex_dump() {
         let file = "Box Data Dump.csv";
-        let team = parse_pkhex_dump(file);
+        let team = parse_pkhex_dump(file, Generation::Gen2To5);
         assert!(team.len() >= 6);
+        // "(None)" and items that do not affect matchups, like Eviolite
+        assert!(team.iter().all(|p| p.item.is_none()));
+    }
+
+    #[test]
+    fn test_individual_stats() {
+        let team = parse_pkhex_dump("Box Data Dump.csv", Generation::Gen2To5);
+        let hariyama = team.iter().find(|p| p.species == "Hariyama").unwrap();
+        let individual = hariyama.individual.as_ref().unwrap();
+        assert_eq!(individual.level, 34);
+        assert_eq!(individual.nature, Nature::Serious);
+        assert_eq!(hariyama.stats().unwrap().hp, 142);
+        // Seismitoad's Attack went from 85 to 95 in gen 7, which the data files use
+        let mismatched = team
+            .iter()
+            .filter(|p| {
+                let individual = p.individual.as_ref().unwrap();
+                individual.calculate(&p.species_data.as_ref().unwrap().base_stats) != individual.stats
+            })
+            .map(|p| p.species.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(mismatched, vec!["Seismitoad"]);
+        let seismitoad = team.iter().find(|p| p.species == "Seismitoad").unwrap();
+        assert_eq!(seismitoad.individual.as_ref().unwrap().mismatched, vec![Stat::Attack]);
+        assert!(hariyama.individual.as_ref().unwrap().mismatched.is_empty());
+    }
+
+    #[test]
+    fn test_parse_names_per_generation() {
+        let gen5 = parse_names(vec!["Clefairy"], Generation::Gen2To5).next().unwrap();
+        let gen6 = parse_names(vec!["Clefairy"], Generation::Gen6Plus).next().unwrap();
+        assert_eq!(gen5.typing, Typing::from(BasicType::Normal));
+        assert_eq!(gen6.typing, Typing::from(BasicType::Fairy));
+    }
+
+    #[test]
+    fn test_forms() {
+        let vulpix = parse_names(vec!["Alolan Vulpix"], Generation::Gen6Plus).collect::<Vec<_>>();
+        assert!(!vulpix.is_empty());
+        assert!(vulpix.iter().all(|p| p.typing == Typing::from(BasicType::Ice)));
+        assert!(parse_names(vec!["Vulpix"], Generation::Gen6Plus).all(|p| p.typing == Typing::from(BasicType::Fire)));
+
+        // Rotom comes with its appliances, as it can change between them
+        let rotom = parse_names(vec!["Rotom"], Generation::Gen2To5).collect::<Vec<_>>();
+        assert_eq!(rotom.iter().map(|p| &p.typing).unique().count(), 6);
+        assert!(parse_names(vec!["Heat Rotom"], Generation::Gen2To5).all(|p| p.typing == Typing::from((BasicType::Electric, BasicType::Fire))));
+        let heat = species_form("Rotom", 1, Generation::Gen2To5);
+        assert_eq!(heat.types, vec![BasicType::Electric, BasicType::Fire]);
+        // Gastrodon's West Sea and East Sea only look different
+        let gastrodon = species_form("Gastrodon", 1, Generation::Gen2To5);
+        assert_eq!(gastrodon.types, vec![BasicType::Water, BasicType::Ground]);
+        // Unown's letters have no data of their own
+        assert_eq!(species_form("Unown", 5, Generation::Gen2To5).form, 0);
     }
 }
@@ -0,0 +1,233 @@
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+use crate::typing::{BasicType, Multiplier, Relationship};
+
+/// Held items that change matchups
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
+pub enum Item {
+    /// Immune to Ground until hit
+    AirBalloon,
+    /// Type immunities are lost
+    RingTarget,
+    /// Grounded, so Flying types and Levitate are hit by Ground
+    IronBall,
+    /// Super effective hits deal 1.2x
+    ExpertBelt,
+    /// Occa Berry and friends halve a super effective hit of the type, Chilan Berry any Normal hit
+    ResistBerry(BasicType),
+    /// Plates and items like Charcoal give moves of the type 1.2x power
+    TypeBoost(BasicType),
+    /// Speed is 1.5x
+    ChoiceScarf,
+}
+
+impl Item {
+    /// Defense after the item, given the holder's defense
+    pub fn apply_defense(&self, defense: Relationship) -> Relationship {
+        match self {
+            Item::AirBalloon => defense.map(|t, m| match t {
+                BasicType::Ground => Multiplier::IMMUNE,
+                _ => m,
+            }),
+            Item::ResistBerry(berry) => defense.map(|t, m| {
+                let triggers =
+                    m > Multiplier::NEUTRAL || (*berry == BasicType::Normal && !m.is_immune());
+                if t == *berry && triggers {
+                    m * Multiplier::RESISTED
+                } else {
+                    m
+                }
+            }),
+            _ => defense,
+        }
+    }
+
+    /// Whether the holder is grounded regardless of its typing and ability
+    pub fn grounds(&self) -> Option<bool> {
+        match self {
+            Item::AirBalloon => Some(false),
+            Item::IronBall => Some(true),
+            _ => None,
+        }
+    }
+
+    /// Whether this item changes the effectiveness of its holder's hits
+    pub fn modifies_attack(&self) -> bool {
+        matches!(self, Item::ExpertBelt)
+    }
+
+    /// Effectiveness of the holder's hits after the item
+    pub fn modify_effectiveness(&self, m: Multiplier) -> Multiplier {
+        match self {
+            Item::ExpertBelt if m > Multiplier::NEUTRAL => m * Multiplier::new(6, 5),
+            _ => m,
+        }
+    }
+
+    /// Speed multiplier for the holder
+    pub fn speed_multiplier(&self) -> Multiplier {
+        match self {
+            Item::ChoiceScarf => Multiplier::new(3, 2),
+            _ => Multiplier::NEUTRAL,
+        }
+    }
+
+    /// Power multiplier for the holder's moves of type `t`
+    pub fn power_multiplier(&self, t: BasicType) -> Multiplier {
+        match self {
+            Item::TypeBoost(boosted) if *boosted == t => Multiplier::new(6, 5),
+            _ => Multiplier::NEUTRAL,
+        }
+    }
+}
+
+impl FromStr for Item {
+    type Err = ();
+
+    /// Parses in-game item names, e.g. "Occa Berry" or "Flame Plate"
+    fn from_str(s: &str) -> Result<Item, ()> {
+        use BasicType::*;
+        let item = match s {
+            "Air Balloon" => Item::AirBalloon,
+            "Ring Target" => Item::RingTarget,
+            "Iron Ball" => Item::IronBall,
+            "Expert Belt" => Item::ExpertBelt,
+            "Choice Scarf" => Item::ChoiceScarf,
+            "Chilan Berry" => Item::ResistBerry(Normal),
+            "Occa Berry" => Item::ResistBerry(Fire),
+            "Passho Berry" => Item::ResistBerry(Water),
+            "Wacan Berry" => Item::ResistBerry(Electric),
+            "Rindo Berry" => Item::ResistBerry(Grass),
+            "Yache Berry" => Item::ResistBerry(Ice),
+            "Chople Berry" => Item::ResistBerry(Fighting),
+            "Kebia Berry" => Item::ResistBerry(Poison),
+            "Shuca Berry" => Item::ResistBerry(Ground),
+            "Coba Berry" => Item::ResistBerry(Flying),
+            "Payapa Berry" => Item::ResistBerry(Psychic),
+            "Tanga Berry" => Item::ResistBerry(Bug),
+            "Charti Berry" => Item::ResistBerry(Rock),
+            "Kasib Berry" => Item::ResistBerry(Ghost),
+            "Haban Berry" => Item::ResistBerry(Dragon),
+            "Colbur Berry" => Item::ResistBerry(Dark),
+            "Babiri Berry" => Item::ResistBerry(Steel),
+            "Roseli Berry" => Item::ResistBerry(Fairy),
+            "Flame Plate" | "Charcoal" => Item::TypeBoost(Fire),
+            "Splash Plate" | "Mystic Water" | "Sea Incense" | "Wave Incense" => {
+                Item::TypeBoost(Water)
+            }
+            "Zap Plate" | "Magnet" => Item::TypeBoost(Electric),
+            "Meadow Plate" | "Miracle Seed" | "Rose Incense" => Item::TypeBoost(Grass),
+            "Icicle Plate" | "Never-Melt Ice" | "NeverMeltIce" => Item::TypeBoost(Ice),
+            "Fist Plate" | "Black Belt" => Item::TypeBoost(Fighting),
+            "Toxic Plate" | "Poison Barb" => Item::TypeBoost(Poison),
+            "Earth Plate" | "Soft Sand" => Item::TypeBoost(Ground),
+            "Sky Plate" | "Sharp Beak" => Item::TypeBoost(Flying),
+            "Mind Plate" | "Twisted Spoon" | "TwistedSpoon" | "Odd Incense" => {
+                Item::TypeBoost(Psychic)
+            }
+            "Insect Plate" | "Silver Powder" | "SilverPowder" => Item::TypeBoost(Bug),
+            "Stone Plate" | "Hard Stone" | "Rock Incense" => Item::TypeBoost(Rock),
+            "Spooky Plate" | "Spell Tag" => Item::TypeBoost(Ghost),
+            "Draco Plate" | "Dragon Fang" => Item::TypeBoost(Dragon),
+            "Dread Plate" | "Black Glasses" | "BlackGlasses" => Item::TypeBoost(Dark),
+            "Iron Plate" | "Metal Coat" => Item::TypeBoost(Steel),
+            "Pixie Plate" | "Fairy Feather" => Item::TypeBoost(Fairy),
+            "Silk Scarf" => Item::TypeBoost(Normal),
+            _ => return Err(()),
+        };
+        Ok(item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        analysis::checks::checks,
+        pokemon::Pokemon,
+        ruleset::Ruleset,
+        typing::{Ability, AttackTrait, BasicType::*, TypeTrait},
+    };
+
+    fn holding(pokemon: Pokemon, item: &str) -> Pokemon {
+        Pokemon {
+            item: Some(Item::from_str(item).unwrap()),
+            ..pokemon
+        }
+    }
+
+    #[test]
+    fn test_defensive_items() {
+        let heatran = Pokemon::from((Fire, Steel));
+        assert_eq!(
+            holding(heatran.clone(), "Air Balloon")
+                .defense()
+                .get(Ground),
+            0.0
+        );
+        assert_eq!(
+            holding(heatran.clone(), "Shuca Berry")
+                .defense()
+                .get(Ground),
+            2.0
+        );
+        // Resist berries only trigger on super effective hits
+        assert_eq!(
+            holding(heatran.clone(), "Occa Berry").defense().get(Fire),
+            1.0
+        );
+        let ferrothorn = holding(Pokemon::from((Grass, Steel)), "Occa Berry");
+        assert_eq!(ferrothorn.defense().get(Fire), 2.0);
+        assert_eq!(
+            holding(Pokemon::from(Ghost), "Chilan Berry")
+                .defense()
+                .get(Normal),
+            0.0
+        );
+        assert_eq!(
+            holding(Pokemon::from(Water), "Chilan Berry")
+                .defense()
+                .get(Normal),
+            0.5
+        );
+        let gengar = holding(Pokemon::from((Ghost, Poison)), "Ring Target");
+        assert_eq!(gengar.defense().get(Normal), 1.0);
+        assert_eq!(gengar.defense().get(Fighting), 0.5);
+        let skarmory = holding(Pokemon::from((Steel, Flying)), "Iron Ball");
+        assert_eq!(skarmory.defense().get(Ground), 2.0);
+        assert!(skarmory.is_grounded());
+        let levitate = Pokemon {
+            ability: Some(Ability::Levitate),
+            ..Pokemon::from(Electric)
+        };
+        assert_eq!(
+            holding(levitate.clone(), "Iron Ball").defense().get(Ground),
+            2.0
+        );
+        assert!(!holding(Pokemon::from(Fire), "Air Balloon").is_grounded());
+        assert!(Item::from_str("Eviolite").is_err());
+    }
+
+    #[test]
+    fn test_offensive_items() {
+        let belt = holding(Pokemon::from(Fighting), "Expert Belt");
+        assert_eq!(belt.effectiveness(&Pokemon::from(Steel)), 2.4);
+        assert_eq!(belt.effectiveness(&Pokemon::from(Water)), 1.0);
+        let plate = Item::from_str("Flame Plate").unwrap();
+        assert_eq!(plate.power_multiplier(Fire), 1.2);
+        assert_eq!(plate.power_multiplier(Water), 1.0);
+    }
+
+    #[test]
+    fn test_checks() {
+        let rules = &Ruleset::default();
+        let excadrill = Pokemon::from((Ground, Steel));
+        assert!(!checks(&Pokemon::from(Fire), &excadrill, rules));
+        assert!(checks(
+            &holding(Pokemon::from(Fire), "Air Balloon"),
+            &excadrill,
+            rules
+        ));
+    }
+}
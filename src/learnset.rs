@@ -0,0 +1,228 @@
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, sync::OnceLock};
+
+use crate::{
+    pokemon::{Move, Pokemon},
+    ruleset::Generation,
+    species::Species,
+    typing::BasicType,
+};
+
+/// How a move is learnt
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum LearnMethod {
+    LevelUp(u8),
+    /// TMs, TRs and HMs
+    Machine,
+    Egg,
+    Tutor,
+}
+
+/// A move a species' form learns in the games of one generation
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Learnable {
+    pub species: String,
+    pub form: u8,
+    pub move_name: String,
+    pub method: LearnMethod,
+    /// Generation of the games the learnset is from
+    pub generation: u8,
+}
+
+impl Learnable {
+    /// Every row of the learnsets file
+    pub fn all() -> &'static Vec<Learnable> {
+        static LEARNSETS: OnceLock<Vec<Learnable>> = OnceLock::new();
+        LEARNSETS.get_or_init(|| {
+            csv::Reader::from_path("data/learnsets.csv")
+                .unwrap()
+                .into_records()
+                .map(|r| Learnable::from_record(&r.unwrap()))
+                .collect()
+        })
+    }
+
+    /// Parses a row of `learnsets.csv`
+    pub fn from_record(record: &csv::StringRecord) -> Learnable {
+        // species,form,move,method,level,generation
+        let field = |i: usize| record.get(i).unwrap_or_default().trim();
+        let number = |i: usize| {
+            field(i)
+                .parse()
+                .unwrap_or_else(|_| panic!("Invalid number {:?} for {:?}", field(i), field(0)))
+        };
+        let method = match field(3) {
+            "level-up" => LearnMethod::LevelUp(number(4)),
+            "machine" => LearnMethod::Machine,
+            "egg" => LearnMethod::Egg,
+            "tutor" => LearnMethod::Tutor,
+            method => panic!("Unknown learn method {method:?}"),
+        };
+        Learnable {
+            species: field(0).to_string(),
+            form: number(1),
+            move_name: field(2).to_string(),
+            method,
+            generation: number(5),
+        }
+    }
+
+    /// The row of `learnsets.csv`, as read by `from_record`
+    pub fn to_record(&self) -> csv::StringRecord {
+        let (method, level) = match self.method {
+            LearnMethod::LevelUp(level) => ("level-up", level.to_string()),
+            LearnMethod::Machine => ("machine", String::new()),
+            LearnMethod::Egg => ("egg", String::new()),
+            LearnMethod::Tutor => ("tutor", String::new()),
+        };
+        csv::StringRecord::from(vec![
+            self.species.clone(),
+            self.form.to_string(),
+            self.move_name.clone(),
+            method.to_string(),
+            level,
+            self.generation.to_string(),
+        ])
+    }
+}
+
+/// Moves the species' form learns, from the latest learnset in the games of the generation.
+/// `None` without a learnset of its own, as opposed to a learnset without any move of the
+/// generation. Pre-evolutions' learnsets aren't used, as evolving changes the moves learnt
+pub fn learnable_moves(
+    species: &Species,
+    generation: Generation,
+) -> Option<Vec<(&'static Move, LearnMethod)>> {
+    let rows = Learnable::all()
+        .iter()
+        .filter(|l| l.species == species.name && l.form == species.form)
+        .filter(|l| {
+            generation
+                .last_game()
+                .is_none_or(|last| l.generation <= last)
+        })
+        .collect::<Vec<_>>();
+    let latest = rows.iter().map(|l| l.generation).max()?;
+    let moves = rows
+        .into_iter()
+        .filter(|l| l.generation == latest)
+        .filter_map(|l| Some((Move::get(&l.move_name)?, l.method)))
+        .filter(|(m, _)| m.available_in(generation))
+        .collect();
+    Some(moves)
+}
+
+/// Whether the species' form can learn the move, by name. `None` without a learnset
+pub fn can_learn(species: &Species, move_name: &str, generation: Generation) -> Option<bool> {
+    let moves = learnable_moves(species, generation)?;
+    Some(moves.iter().any(|(m, _)| m.name == move_name))
+}
+
+/// Damaging moves the species' form can learn by type, strongest first and variable power ones
+/// like Low Kick last. `None` without a learnset
+pub fn damaging_moves_by_type(
+    species: &Species,
+    generation: Generation,
+) -> Option<BTreeMap<BasicType, Vec<&'static Move>>> {
+    let by_type = learnable_moves(species, generation)?
+        .into_iter()
+        .map(|(m, _)| m)
+        .filter(|m| !m.is_status())
+        .unique()
+        .sorted_by_key(|m| std::cmp::Reverse(m.power))
+        .into_group_map_by(|m| m.typing)
+        .into_iter()
+        .collect();
+    Some(by_type)
+}
+
+/// The Pokemon with every damaging move it can learn when its own moves are unknown. Left as is
+/// without a learnset
+pub fn with_potential_moves(pokemon: &Pokemon, generation: Generation) -> Pokemon {
+    let potential = pokemon
+        .species_data
+        .as_ref()
+        .filter(|_| pokemon.moves.is_empty())
+        .and_then(|species| damaging_moves_by_type(species, generation));
+    let moves = match potential {
+        Some(by_type) => by_type.into_values().flatten().cloned().collect(),
+        None => pokemon.moves.clone(),
+    };
+    Pokemon {
+        moves,
+        ..pokemon.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::typing::BasicType::*;
+
+    fn species(name: &str) -> &'static Species {
+        Species::get(Generation::Gen6Plus, name, 0).unwrap()
+    }
+
+    #[test]
+    fn test_learnable_moves() {
+        let generation = Generation::Gen6Plus;
+        let scraggy = species("Scraggy");
+        assert!(learnable_moves(scraggy, generation).unwrap().contains(&(
+            Move::get("High Jump Kick").unwrap(),
+            LearnMethod::LevelUp(32)
+        )));
+        assert_eq!(can_learn(scraggy, "Drain Punch", generation), Some(true));
+        assert_eq!(can_learn(scraggy, "Surf", generation), Some(false));
+        // Scrafty has no learnset of its own and doesn't take Scraggy's
+        assert_eq!(learnable_moves(species("Scrafty"), generation), None);
+        let garchomp = species("Garchomp");
+        assert_eq!(can_learn(garchomp, "Earthquake", generation), Some(true));
+        assert_eq!(can_learn(garchomp, "Fire Fang", generation), Some(true));
+        // No learnset at all is not the same as learning nothing
+        assert_eq!(learnable_moves(species("Unown"), generation), None);
+        assert_eq!(
+            can_learn(species("Unown"), "Hidden Power", generation),
+            None
+        );
+
+        let by_type = damaging_moves_by_type(scraggy, generation).unwrap();
+        assert!(by_type.contains_key(&Fighting) && by_type.contains_key(&Dark));
+        assert!(by_type[&Fighting][0].power >= by_type[&Fighting][1].power);
+        // Low Kick has no fixed power but still hits
+        assert_eq!(by_type[&Fighting].last().unwrap().name, "Low Kick");
+        assert!(by_type.values().flatten().all(|m| !m.is_status()));
+
+        // Only learnsets from the generation's games
+        let pansage = Species::get(Generation::Gen2To5, "Pansage", 0).unwrap();
+        assert_eq!(
+            can_learn(pansage, "Seed Bomb", Generation::Gen2To5),
+            Some(true)
+        );
+        let gyarados = Species::get(Generation::Gen2To5, "Gyarados", 0).unwrap();
+        assert_eq!(learnable_moves(gyarados, Generation::Gen2To5), None);
+    }
+
+    #[test]
+    fn test_records() {
+        for learnable in Learnable::all() {
+            assert_eq!(&Learnable::from_record(&learnable.to_record()), learnable);
+        }
+    }
+
+    #[test]
+    fn test_potential_moves() {
+        let generation = Generation::Gen6Plus;
+        let comfey =
+            Pokemon::of_species(Species::get(generation, "Comfey", 0).unwrap(), generation)
+                .remove(0);
+        let potential = with_potential_moves(&comfey, generation);
+        assert!(potential.moves.iter().any(|m| m.name == "Draining Kiss"));
+        // Known moves are kept
+        let tackle = Pokemon {
+            moves: vec![Move::get("Tackle").unwrap().clone()],
+            ..comfey.clone()
+        };
+        assert_eq!(with_potential_moves(&tackle, generation), tackle);
+    }
+}
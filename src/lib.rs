@@ -2,4 +2,14 @@ pub mod pokemon;
 pub mod typing;
 pub mod team;
 pub mod analysis;
-pub mod injest;
\ No newline at end of file
+pub mod injest;
+pub mod ruleset;
+pub mod field;
+pub mod item;
+pub mod species;
+pub mod stats;
+pub mod damage;
+pub mod evolution;
+pub mod learnset;
+#[cfg(test)]
+mod fixtures;
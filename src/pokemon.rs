@@ -1,9 +1,23 @@
-use crate::typing::{combine_defense_charts, Ability, BasicType, Relationship, TypeTrait};
+use crate::{
+    item::Item,
+    ruleset::Generation,
+    species::{FormKind, Species},
+    stats::{Individual, Stats},
+    typing::{
+        combine_defense_charts, Ability, Attack, AttackTrait, BasicType, Multiplier, Relationship,
+        SpecialMove, TeraType, TypeChart, TypeTrait,
+    },
+};
 use itertools::Itertools;
 use rand::seq::SliceRandom;
 use serde::{Deserialize, Serialize};
-use std::{collections::BTreeSet, str::FromStr, sync::OnceLock};
-use strum::IntoEnumIterator;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    str::FromStr,
+    sync::{Arc, OnceLock},
+};
+use strum::{EnumCount, EnumString, IntoEnumIterator};
 
 #[derive(Clone, Debug, PartialEq, Eq, Ord, PartialOrd, Hash, Deserialize, Serialize)]
 pub struct Typing(BTreeSet<BasicType>);
@@ -33,61 +47,276 @@ impl From<(BasicType, BasicType)> for Typing {
 }
 
 impl Typing {
-    fn mono() -> impl Iterator<Item = Typing> {
-        BasicType::iter().map(Typing::from)
+    fn mono(generation: Generation) -> impl Iterator<Item = Typing> {
+        generation.chart().types().map(Typing::from)
     }
-    fn dual() -> impl Iterator<Item = Typing> {
-        BasicType::iter()
+    fn dual(generation: Generation) -> impl Iterator<Item = Typing> {
+        generation
+            .chart()
+            .types()
             .combinations(2)
             .map(|c| Typing::from((c[0], c[1])))
     }
-    fn all() -> impl Iterator<Item = Typing> {
-        Typing::mono().chain(Typing::dual())
+    fn all(generation: Generation) -> impl Iterator<Item = Typing> {
+        Typing::mono(generation).chain(Typing::dual(generation))
     }
 }
 
+impl Typing {
+    /// Typing of the species in the generation.
+    /// Types added in later generations are dropped, e.g. Magnemite is pure Electric in gen 1
+    pub fn of(species: &Species, generation: Generation) -> Typing {
+        let types = generation.chart().types().collect::<BTreeSet<_>>();
+        Typing(
+            species
+                .types
+                .iter()
+                .copied()
+                .filter(|t| types.contains(t))
+                .collect(),
+        )
+    }
+
+    /// Typing after the changes are applied in order
+    pub fn changed(&self, changes: impl IntoIterator<Item = TypeChange>) -> Typing {
+        let mut types = self.0.clone();
+        let mut added = None;
+        for change in changes {
+            match change {
+                TypeChange::Add(t) => {
+                    // Replaces any type added before
+                    if let Some(previous) = added.take() {
+                        types.remove(&previous);
+                    }
+                    if types.insert(t) {
+                        added = Some(t);
+                    }
+                }
+                TypeChange::Replace(t) => {
+                    types = BTreeSet::from([t]);
+                    added = None;
+                }
+                TypeChange::Remove(t) => {
+                    types.remove(&t);
+                }
+                TypeChange::Roost => {
+                    if types.remove(&BasicType::Flying) && types.is_empty() {
+                        types.insert(BasicType::Normal);
+                    }
+                }
+            }
+        }
+        Typing(types)
+    }
+}
+
+/// Typeless Pokemon, e.g. a pure Fire type after Burn Up, take neutral damage from everything
 impl TypeTrait for Typing {
-    fn defense(&self) -> Relationship {
-        match self.0.iter().collect::<Vec<_>>().as_slice() {
-            [t] => t.defense(),
-            [t1, t2] => combine_defense_charts(vec![t1.defense(), t2.defense()]),
-            _ => panic!("Invalid typing"),
+    fn defense_with(&self, chart: &TypeChart) -> Relationship {
+        combine_defense_charts(self.0.iter().map(|t| t.defense_with(chart)))
+    }
+
+    fn is_grounded(&self) -> bool {
+        self.0.iter().all(|t| t.is_grounded())
+    }
+}
+
+/// Battle effects that change a Pokemon's typing
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
+pub enum TypeChange {
+    /// Forest's Curse and Trick-or-Treat add a third type
+    Add(BasicType),
+    /// Soak and Magic Powder replace the typing with a single type
+    Replace(BasicType),
+    /// Burn Up and Double Shock remove the user's type
+    Remove(BasicType),
+    /// Removes Flying for the turn, pure Flying types become Normal
+    Roost,
+}
+
+impl TypeChange {
+    /// Type change caused by a move, by name
+    pub fn of_move(name: &str) -> Option<TypeChange> {
+        let name = name
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .collect::<String>()
+            .to_lowercase();
+        match name.as_str() {
+            "forestscurse" => Some(TypeChange::Add(BasicType::Grass)),
+            "trickortreat" => Some(TypeChange::Add(BasicType::Ghost)),
+            "soak" => Some(TypeChange::Replace(BasicType::Water)),
+            "magicpowder" => Some(TypeChange::Replace(BasicType::Psychic)),
+            "roost" => Some(TypeChange::Roost),
+            "burnup" => Some(TypeChange::Remove(BasicType::Fire)),
+            "doubleshock" => Some(TypeChange::Remove(BasicType::Electric)),
+            _ => None,
         }
     }
 }
 
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumString, Deserialize, Serialize,
+)]
+pub enum DamageClass {
+    Physical,
+    Special,
+    Status,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
 pub struct Move {
     pub name: String,
+    pub id: u32,
     pub typing: BasicType,
+    /// `None` for moves that skip the accuracy check
+    pub accuracy: Option<u32>,
     pub power: Option<u32>,
+    pub pp: u32,
+    pub priority: i8,
+    /// Game generation the move was introduced in
+    pub generation: u8,
+    pub damage_class: DamageClass,
+    pub description: String,
+}
+
+/// Why a row of the moves file could not be read
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MoveError {
+    UnknownType(String),
+    UnknownDamageClass(String),
+    UnknownGeneration(String),
+    InvalidNumber { field: &'static str, value: String },
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveError::UnknownType(t) => write!(f, "unknown type {t:?}"),
+            MoveError::UnknownDamageClass(c) => write!(f, "unknown damage class {c:?}"),
+            MoveError::UnknownGeneration(g) => write!(f, "unknown generation {g:?}"),
+            MoveError::InvalidNumber { field, value } => write!(f, "invalid {field} {value:?}"),
+        }
+    }
 }
 
+impl std::error::Error for MoveError {}
+
 impl Move {
     pub fn all() -> Vec<Move> {
-        // name,id,accuracy,pp,power,priority,type,generation,short_descripton,damage_class
         let file = "data/metadata_pokemon_moves.csv";
         let mut rdr = csv::Reader::from_path(file).unwrap();
         rdr.records()
-            .filter_map(|r| {
+            .map(|r| {
                 let record = r.unwrap();
-                let name = record.get(0).unwrap().to_string();
-                let typing = match BasicType::from_str(record.get(6).unwrap()) {
-                    Ok(t) => t,
-                    Err(_) => BasicType::Normal,
-                };
-                let power = match record.get(4).unwrap() {
-                    "" => None,
-                    p => Some(p.parse::<f32>().unwrap() as u32),
-                };
-                Some(Move {
-                    name,
-                    typing,
-                    power,
-                })
+                Move::from_record(&record)
+                    .unwrap_or_else(|e| panic!("Could not read move {:?}: {e}", record.get(0)))
             })
             .collect()
     }
+
+    /// Move of the moves file by name
+    pub fn get(name: &str) -> Option<&'static Move> {
+        static MOVES: OnceLock<BTreeMap<String, Move>> = OnceLock::new();
+        MOVES
+            .get_or_init(|| {
+                Move::all()
+                    .into_iter()
+                    .map(|m| (m.name.clone(), m))
+                    .collect()
+            })
+            .get(name)
+    }
+
+    /// Parses a row of `metadata_pokemon_moves.csv`
+    pub fn from_record(record: &csv::StringRecord) -> Result<Move, MoveError> {
+        // name,id,accuracy,pp,power,priority,type,generation,short_descripton,damage_class
+        let field = |i: usize| record.get(i).unwrap_or_default();
+        // Numbers are written as floats, e.g. "100.0"
+        let number = |i: usize, name: &'static str| -> Result<Option<f32>, MoveError> {
+            match field(i) {
+                "" => Ok(None),
+                n => n
+                    .parse::<f32>()
+                    .map(Some)
+                    .map_err(|_| MoveError::InvalidNumber {
+                        field: name,
+                        value: n.to_string(),
+                    }),
+            }
+        };
+        let required = |i: usize, name: &'static str| -> Result<f32, MoveError> {
+            number(i, name)?.ok_or(MoveError::InvalidNumber {
+                field: name,
+                value: "".into(),
+            })
+        };
+        let typing = BasicType::from_str(field(6))
+            .map_err(|_| MoveError::UnknownType(field(6).to_string()))?;
+        let damage_class = DamageClass::from_str(field(9))
+            .map_err(|_| MoveError::UnknownDamageClass(field(9).to_string()))?;
+        let generation = field(7)
+            .strip_prefix("Generation ")
+            .and_then(|numeral| {
+                let numeral = numeral.to_lowercase();
+                ["i", "ii", "iii", "iv", "v", "vi", "vii", "viii", "ix"]
+                    .iter()
+                    .position(|n| *n == numeral)
+            })
+            .ok_or_else(|| MoveError::UnknownGeneration(field(7).to_string()))?
+            as u8
+            + 1;
+        Ok(Move {
+            name: field(0).to_string(),
+            id: required(1, "id")? as u32,
+            typing,
+            accuracy: number(2, "accuracy")?.map(|a| a as u32),
+            power: number(4, "power")?.map(|p| p as u32),
+            pp: required(3, "pp")? as u32,
+            priority: required(5, "priority")? as i8,
+            generation,
+            damage_class,
+            description: field(8).to_string(),
+        })
+    }
+
+    pub fn is_status(&self) -> bool {
+        self.damage_class == DamageClass::Status
+    }
+
+    /// Whether the move exists in the games of the generation
+    pub fn available_in(&self, generation: Generation) -> bool {
+        generation
+            .last_game()
+            .is_none_or(|last| self.generation <= last)
+    }
+}
+
+impl Move {
+    /// Exception to the type chart this move has, if any
+    pub fn special(&self) -> Option<SpecialMove> {
+        SpecialMove::from_str(&self.name).ok()
+    }
+}
+
+impl AttackTrait for Move {
+    fn attacking_types(&self) -> Vec<BasicType> {
+        // Status moves deal no damage
+        match self.damage_class {
+            DamageClass::Status => vec![],
+            DamageClass::Physical | DamageClass::Special => vec![self.typing],
+        }
+    }
+
+    /// Honours the move's own exceptions, which `effectiveness_against` cannot see
+    fn effectiveness_with(&self, defender: &impl TypeTrait, chart: &TypeChart) -> Multiplier {
+        match self.special() {
+            Some(special) if !self.is_status() => {
+                special.effectiveness_with(self.typing, defender, chart)
+            }
+            _ => self.effectiveness_against(&defender.defense_with(chart)),
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
@@ -96,6 +325,20 @@ pub struct Pokemon {
     pub typing: Typing,
     pub ability: Option<Ability>,
     pub moves: Vec<Move>,
+    /// Terastallized type, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tera: Option<TeraType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub item: Option<Item>,
+    /// Base stats and Pokedex data, shared by every Pokemon of the species
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub species_data: Option<Arc<Species>>,
+    /// Level, nature, IVs and EVs, when known from a save
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub individual: Option<Individual>,
+    /// What it becomes after Mega Evolving or another in-battle form change
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transformed: Option<Box<Pokemon>>,
 }
 
 impl From<BasicType> for Pokemon {
@@ -105,6 +348,11 @@ impl From<BasicType> for Pokemon {
             typing: t.into(),
             ability: None,
             moves: vec![],
+            tera: None,
+            item: None,
+            species_data: None,
+            individual: None,
+            transformed: None,
         }
     }
 }
@@ -116,6 +364,11 @@ impl From<(BasicType, BasicType)> for Pokemon {
             typing: t.into(),
             ability: None,
             moves: vec![],
+            tera: None,
+            item: None,
+            species_data: None,
+            individual: None,
+            transformed: None,
         }
     }
 }
@@ -132,73 +385,108 @@ pub trait PokemonIteratorHelper: Iterator<Item = Pokemon> {
 impl<I> PokemonIteratorHelper for I where I: Iterator<Item = Pokemon> + ?Sized {}
 
 impl Pokemon {
-    pub fn all() -> &'static Vec<Pokemon> {
-        static CELL: OnceLock<Vec<Pokemon>> = OnceLock::new();
-        CELL.get_or_init(|| {
-            // dexnum,name,generation,type1,type2,species,height,weight,ability1,ability2,hidden_ability,hp,attack,defense,sp_atk,sp_def,speed,total,ev_yield,catch_rate,base_friendship,base_exp,growth_rate,egg_group1,egg_group2,percent_male,percent_female,egg_cycles,special_group
-            let file = "data/pokemon_data_gen5.csv";
-            #[cfg(feature = "gen6")]
-            let file = "data/pokemon_data_gen6+.csv";
-            csv::Reader::from_path(file)
-                .unwrap()
-                .records()
-                .flat_map(|r| {
-                    let record = r.unwrap();
-                    let name = record.get(1).unwrap().to_string();
-                    let typing = match record.get(4).unwrap() {
-                        "" => Typing::from(BasicType::from_str(record.get(3).unwrap()).unwrap()),
-                        t => Typing::from((
-                            BasicType::from_str(record.get(3).unwrap()).unwrap(),
-                            BasicType::from_str(t).unwrap(),
-                        )),
-                    };
-                    let abilities: Vec<Option<Ability>> = vec![
-                        record.get(8).unwrap(),
-                        record.get(9).unwrap(),
-                        record.get(10).unwrap(),
-                    ]
-                    .into_iter()
-                    .map(|a| match a {
-                        "" => None,
-                        a => match Ability::from_str(a) {
-                            Ok(a) => Some(a),
-                            Err(_) => None,
-                        },
-                    })
-                    .collect();
-                    abilities.into_iter().map(move |a| Pokemon {
-                        species: name.clone(),
-                        typing: typing.clone(),
-                        ability: a,
-                        moves: vec![],
-                    })
-                })
-                .chain(
-                    // Rotom forms
-                    vec![
-                        (BasicType::Electric, BasicType::Fire),
-                        (BasicType::Electric, BasicType::Water),
-                        (BasicType::Electric, BasicType::Ice),
-                        (BasicType::Electric, BasicType::Flying),
-                        (BasicType::Electric, BasicType::Grass),
-                    ]
-                    .into_iter()
-                    .map(|t| Pokemon {
-                        species: "Rotom".into(),
-                        typing: t.into(),
-                        ability: Some(Ability::Levitate),
-                        moves: vec![],
-                    }),
-                )
+    /// Every Pokemon outside of battle, one per ability. Battle-only forms like Megas are left out
+    pub fn all(generation: Generation) -> &'static Vec<Pokemon> {
+        static CELLS: [OnceLock<Vec<Pokemon>>; Generation::COUNT] =
+            [const { OnceLock::new() }; Generation::COUNT];
+        CELLS[generation as usize].get_or_init(|| {
+            Species::all(generation)
+                .iter()
+                .filter(|s| !s.form_kind.is_battle_only())
+                .flat_map(|s| Pokemon::of_species(s, generation))
                 .collect()
         })
     }
 
+    /// The species or form with each of its abilities
+    pub fn of_species(species: &Arc<Species>, generation: Generation) -> Vec<Pokemon> {
+        let abilities: Vec<Option<Ability>> = if generation.has_abilities() {
+            species
+                .abilities
+                .iter()
+                .map(|a| Ability::from_str(a).ok())
+                .collect()
+        } else {
+            vec![None]
+        };
+        let typing = Typing::of(species, generation);
+        abilities
+            .into_iter()
+            .map(|a| Pokemon {
+                species: species.name.clone(),
+                typing: typing.clone(),
+                ability: a,
+                moves: vec![],
+                tera: None,
+                item: None,
+                species_data: Some(species.clone()),
+                individual: None,
+                transformed: None,
+            })
+            .collect()
+    }
+
+    /// Battle-only forms like Megas and Primals, one per ability
+    pub fn battle_forms(generation: Generation) -> Vec<Pokemon> {
+        Species::all(generation)
+            .iter()
+            .filter(|s| s.form_kind.is_battle_only())
+            .flat_map(|s| Pokemon::of_species(s, generation))
+            .collect()
+    }
+
+    /// Whether `form` is a battle-only form its base form can change into
+    pub fn can_become(&self, form: &Pokemon) -> bool {
+        form.species == self.species
+            && form
+                .species_data
+                .as_ref()
+                .is_some_and(|s| s.form_kind.is_battle_only())
+            && self
+                .species_data
+                .as_ref()
+                .is_some_and(|s| s.form_kind == FormKind::Base)
+    }
+
+    /// This Pokemon set to change into `form` in battle, keeping its moves, item, Tera type,
+    /// level, nature, IVs and EVs
+    pub fn with_form_change(&self, form: &Pokemon) -> Pokemon {
+        let individual = self
+            .individual
+            .as_ref()
+            .zip(form.species_data.as_ref())
+            .map(|(i, s)| Individual::new(i.level, i.nature, i.ivs, i.evs, &s.base_stats));
+        Pokemon {
+            transformed: Some(Box::new(Pokemon {
+                moves: self.moves.clone(),
+                tera: self.tera,
+                item: self.item,
+                individual,
+                transformed: None,
+                ..form.clone()
+            })),
+            ..self.clone()
+        }
+    }
+
+    /// The Pokemon after its form change, or itself without one
+    pub fn transformed(&self) -> &Pokemon {
+        self.transformed.as_deref().unwrap_or(self)
+    }
+
+    /// Name of its form, e.g. "Alolan Vulpix", or else of its species
+    pub fn name(&self) -> &str {
+        self.species_data
+            .as_ref()
+            .map_or(&self.species, |s| s.display_name())
+    }
+
     // All pokemon, unique by typing and ability
-    pub fn all_unique_type_chart() -> &'static Vec<Pokemon> {
-        static CELL: OnceLock<Vec<Pokemon>> = OnceLock::new();
-        CELL.get_or_init(|| {
-            Pokemon::all()
+    pub fn all_unique_type_chart(generation: Generation) -> &'static Vec<Pokemon> {
+        static CELLS: [OnceLock<Vec<Pokemon>>; Generation::COUNT] =
+            [const { OnceLock::new() }; Generation::COUNT];
+        CELLS[generation as usize].get_or_init(|| {
+            Pokemon::all(generation)
                 .clone()
                 .into_iter()
                 .unique_by_type_ability()
@@ -206,61 +494,185 @@ impl Pokemon {
         })
     }
 
-    pub fn all_type_combinations_and_abilities() -> impl Iterator<Item = Pokemon> {
-        // All monotype/dualtype and ability combinations
-        Typing::all()
-            .flat_map(|t| {
-                Ability::iter().map(move |a| Pokemon {
-                    species: "".into(),
-                    typing: t.clone(),
-                    ability: Some(a),
-                    moves: vec![],
-                })
+    pub fn all_type_combinations_and_abilities(
+        generation: Generation,
+    ) -> impl Iterator<Item = Pokemon> {
+        // All monotype/dualtype and defensive ability combinations, abilities that only change
+        // the holder's hits defend like no ability
+        Typing::all(generation)
+            .flat_map(move |t| {
+                Ability::iter()
+                    .filter(move |a| generation.has_abilities() && !a.modifies_attack())
+                    .map(move |a| Pokemon {
+                        species: "".into(),
+                        typing: t.clone(),
+                        ability: Some(a),
+                        moves: vec![],
+                        tera: None,
+                        item: None,
+                        species_data: None,
+                        individual: None,
+                        transformed: None,
+                    })
             })
-            .chain(Typing::all().map(|t| Pokemon {
+            .chain(Typing::all(generation).map(|t| Pokemon {
                 species: "".into(),
                 typing: t,
                 ability: None,
                 moves: vec![],
+                tera: None,
+                item: None,
+                species_data: None,
+                individual: None,
+                transformed: None,
             }))
     }
 
-    pub fn all_type_combinations() -> impl Iterator<Item = Pokemon> {
-        Typing::all().map(|t| Pokemon {
+    pub fn all_type_combinations(generation: Generation) -> impl Iterator<Item = Pokemon> {
+        Typing::all(generation).map(|t| Pokemon {
             species: "".into(),
             typing: t,
             ability: None,
             moves: vec![],
+            tera: None,
+            item: None,
+            species_data: None,
+            individual: None,
+            transformed: None,
         })
     }
 
-    pub fn random(pool: &Vec<Pokemon>) -> Pokemon {
+    pub fn random(pool: &[Pokemon]) -> Pokemon {
         let mut rng = rand::thread_rng();
         pool.choose(&mut rng).unwrap().clone()
     }
 
-    pub fn random_team(pool: &Vec<Pokemon>, size: usize) -> Vec<Pokemon> {
+    pub fn random_team(pool: &[Pokemon], size: usize) -> Vec<Pokemon> {
         pool.choose_multiple(&mut rand::thread_rng(), size)
             .cloned()
             .sorted()
             .collect()
     }
 
-    pub fn random_fill(team: &Vec<Pokemon>, pool: &Vec<Pokemon>, size: usize) -> Vec<Pokemon> {
+    pub fn random_fill(team: &[Pokemon], pool: &[Pokemon], size: usize) -> Vec<Pokemon> {
         let missing = size - team.len();
-        let mut new_team = team.clone();
+        let mut new_team = team.to_vec();
         new_team.extend(Pokemon::random_team(pool, missing));
         new_team.into_iter().sorted().collect()
     }
 }
 
 impl TypeTrait for Pokemon {
-    fn defense(&self) -> Relationship {
+    fn defense_with(&self, chart: &TypeChart) -> Relationship {
+        let grounded_chart;
+        let chart = match self.item {
+            Some(Item::IronBall) => {
+                grounded_chart = chart.grounded();
+                &grounded_chart
+            }
+            _ => chart,
+        };
+        let typing = match self.item {
+            // Immunities of each type are lost, not of the combination
+            Some(Item::RingTarget) => {
+                combine_defense_charts(self.defensive_typing().iter().map(|t| {
+                    t.defense_with(chart).map(|_, m| {
+                        if m.is_immune() {
+                            Multiplier::NEUTRAL
+                        } else {
+                            m
+                        }
+                    })
+                }))
+            }
+            _ => self.defensive_typing().defense_with(chart),
+        };
+        let defense = match self.ability {
+            Some(a) => a.apply_defense(typing, chart),
+            None => typing,
+        };
+        match self.item {
+            Some(item) => item.apply_defense(defense),
+            None => defense,
+        }
+    }
+
+    fn is_grounded(&self) -> bool {
+        match self.item.and_then(|item| item.grounds()) {
+            Some(grounded) => grounded,
+            None => {
+                self.defensive_typing().is_grounded()
+                    && self.ability.is_none_or(|a| a.is_grounded())
+            }
+        }
+    }
+}
+
+impl Pokemon {
+    /// This Pokemon after its typing changes, which a Tera type is immune to
+    pub fn with_type_changes(&self, changes: impl IntoIterator<Item = TypeChange>) -> Pokemon {
+        Pokemon {
+            typing: self.typing.changed(changes),
+            ..self.clone()
+        }
+    }
+
+    /// Actual stats of this individual, when known
+    pub fn stats(&self) -> Option<Stats> {
+        self.individual.as_ref().map(|i| i.stats)
+    }
+
+    /// Typing it defends with, which is the Tera type once Terastallized
+    pub fn defensive_typing(&self) -> Typing {
+        match self.tera {
+            Some(TeraType::Basic(t)) => Typing::from(t),
+            Some(TeraType::Stellar) | None => self.typing.clone(),
+        }
+    }
+
+    /// STAB types, the original typing plus the Tera type
+    pub fn stabs(&self) -> BTreeSet<BasicType> {
+        let mut stabs = self.typing.0.clone();
+        if let Some(TeraType::Basic(t)) = self.tera {
+            stabs.insert(t);
+        }
+        stabs
+    }
+
+    /// STAB attacks with the ability they are used with
+    pub fn attacks(&self) -> Vec<Attack> {
+        self.attacking_types()
+            .into_iter()
+            .map(|typing| Attack {
+                typing,
+                ability: self.attacker_ability(),
+                item: self.attacker_item(),
+            })
+            .collect()
+    }
+}
+
+impl AttackTrait for Pokemon {
+    /// STAB types, after type changing abilities
+    fn attacking_types(&self) -> Vec<BasicType> {
         match self.ability {
-            Some(a) => combine_defense_charts(vec![self.typing.defense(), a.defense()]),
-            None => self.typing.defense(),
+            Some(a) => self
+                .stabs()
+                .into_iter()
+                .map(|t| a.attack_type(t))
+                .unique()
+                .collect(),
+            None => self.stabs().into_iter().collect(),
         }
     }
+
+    fn attacker_ability(&self) -> Option<Ability> {
+        self.ability.filter(|a| a.modifies_attack())
+    }
+
+    fn attacker_item(&self) -> Option<Item> {
+        self.item.filter(|i| i.modifies_attack())
+    }
 }
 
 #[cfg(test)]
@@ -270,12 +682,75 @@ mod tests {
 
     #[test]
     fn test_pokemon_all() {
-        let all_pokemon = Pokemon::all();
-        // Note that pokemon with multiple abilities that affect the type chart are split into multiple entries
-        let expected_count = 1952;
-        #[cfg(feature = "gen6")]
-        let expected_count = 3080;
-        assert_eq!(all_pokemon.len(), expected_count);
+        // One entry per listed ability of each form, Megas and Primals aside
+        assert_eq!(Pokemon::all(Generation::Gen2To5).len(), 1635);
+        assert_eq!(Pokemon::all(Generation::Gen6Plus).len(), 2611);
+        // The counts used to be 1952 and 3080: three entries per species, one per ability slot,
+        // and Rotom's five appliances. Every species is still there
+        let species = |generation| {
+            Pokemon::all(generation)
+                .iter()
+                .map(|p| &p.species)
+                .unique()
+                .count()
+        };
+        assert_eq!(species(Generation::Gen2To5), 649);
+        assert_eq!(species(Generation::Gen6Plus), 1025);
+    }
+
+    #[test]
+    fn test_form_change() {
+        let forms = Pokemon::battle_forms(Generation::Gen6Plus);
+        assert!(Pokemon::battle_forms(Generation::Gen2To5).is_empty());
+        let altaria = Pokemon::all(Generation::Gen6Plus)
+            .iter()
+            .find(|p| p.species == "Altaria")
+            .unwrap();
+        let mega = forms.iter().find(|f| altaria.can_become(f)).unwrap();
+        assert_eq!(mega.name(), "Mega Altaria");
+        let planned = altaria.with_form_change(mega);
+        assert_eq!(planned.typing, altaria.typing);
+        let transformed = planned.transformed();
+        assert_eq!(
+            transformed.typing,
+            Typing::from((BasicType::Dragon, BasicType::Fairy))
+        );
+        assert_eq!(transformed.ability, Some(Ability::Pixilate));
+        assert!(transformed.transformed.is_none());
+        assert_eq!(altaria.transformed(), altaria);
+        // Neither other species nor forms can take it
+        let charizard = Pokemon::all(Generation::Gen6Plus)
+            .iter()
+            .find(|p| p.species == "Charizard")
+            .unwrap();
+        assert!(!charizard.can_become(mega));
+        assert!(!transformed.can_become(mega));
+        assert_eq!(forms.iter().filter(|f| charizard.can_become(f)).count(), 2);
+    }
+
+    #[test]
+    fn test_type_combinations() {
+        assert_eq!(
+            Pokemon::all_type_combinations(Generation::Gen1).count(),
+            15 + 15 * 14 / 2
+        );
+        assert_eq!(
+            Pokemon::all_type_combinations(Generation::Gen6Plus).count(),
+            18 + 18 * 17 / 2
+        );
+        assert_eq!(
+            Pokemon::all_type_combinations_and_abilities(Generation::Gen1).count(),
+            Pokemon::all_type_combinations(Generation::Gen1).count()
+        );
+        let defensive = Ability::iter().filter(|a| !a.modifies_attack()).count();
+        assert_eq!(
+            Pokemon::all_type_combinations_and_abilities(Generation::Gen6Plus).count(),
+            Pokemon::all_type_combinations(Generation::Gen6Plus).count() * (defensive + 1)
+        );
+        assert!(
+            Pokemon::all_type_combinations_and_abilities(Generation::Gen6Plus)
+                .all(|p| p.ability != Some(Ability::Scrappy))
+        );
     }
 
     #[test]
@@ -315,10 +790,147 @@ mod tests {
         let moves = Move::all();
         assert_eq!(moves[0].power, Some(40));
 
-        let expected_move_count = 808;
-        #[cfg(feature = "gen6")]
         let expected_move_count = 808;
 
         assert_eq!(moves.len(), expected_move_count);
+
+        let quick_attack = moves.iter().find(|m| m.name == "Quick Attack").unwrap();
+        assert_eq!(quick_attack.priority, 1);
+        assert_eq!(quick_attack.damage_class, DamageClass::Physical);
+        assert_eq!(quick_attack.accuracy, Some(100));
+        assert_eq!(quick_attack.pp, 30);
+        let swords_dance = moves.iter().find(|m| m.name == "Swords Dance").unwrap();
+        assert!(swords_dance.is_status());
+        assert_eq!(swords_dance.accuracy, None);
+        // Variable power moves still deal typed damage
+        let seismic_toss = moves.iter().find(|m| m.name == "Seismic Toss").unwrap();
+        assert_eq!(seismic_toss.power, None);
+        assert_eq!(seismic_toss.attacking_types(), vec![BasicType::Fighting]);
+        let moonblast = moves.iter().find(|m| m.name == "Moonblast").unwrap();
+        assert_eq!(moonblast.generation, 6);
+        assert!(!moonblast.available_in(Generation::Gen2To5));
+        assert!(moonblast.available_in(Generation::Gen6Plus));
+        assert!(moves.iter().any(|m| m.generation == 8));
+    }
+
+    #[test]
+    fn test_move_errors() {
+        let record = |t: &str, class: &str, generation: &str| {
+            csv::StringRecord::from(vec![
+                "Test", "1", "", "10", "50.0", "0", t, generation, "", class,
+            ])
+        };
+        assert!(Move::from_record(&record("Fire", "Special", "Generation Iv")).is_ok());
+        assert_eq!(
+            Move::from_record(&record("Shadow", "Special", "Generation Iv")),
+            Err(MoveError::UnknownType("Shadow".into()))
+        );
+        assert_eq!(
+            Move::from_record(&record("Fire", "Other", "Generation Iv")),
+            Err(MoveError::UnknownDamageClass("Other".into()))
+        );
+        assert_eq!(
+            Move::from_record(&record("Fire", "Special", "Generation X")),
+            Err(MoveError::UnknownGeneration("Generation X".into()))
+        );
+    }
+
+    #[test]
+    fn test_attack() {
+        use BasicType::*;
+        let moves = Move::all();
+        let earthquake = moves.iter().find(|m| m.name == "Earthquake").unwrap();
+        let growl = moves.iter().find(|m| m.name == "Growl").unwrap();
+        let heatran = Pokemon::from((Fire, Steel));
+        assert_eq!(earthquake.effectiveness(&heatran), 4.0);
+        assert_eq!(growl.effectiveness(&heatran), 0.0);
+        // Best of both STABs
+        let skarmory = Pokemon::from((Steel, Flying));
+        assert_eq!(
+            skarmory.effectiveness(&Pokemon::from((Grass, Fighting))),
+            4.0
+        );
+        assert_eq!(skarmory.effectiveness(&heatran), 0.5);
+        let freeze_dry = moves
+            .iter()
+            .find(|m| m.special() == Some(SpecialMove::FreezeDry))
+            .unwrap();
+        assert_eq!(
+            freeze_dry.effectiveness(&Pokemon::from((Water, Flying))),
+            4.0
+        );
+        let flying_press = moves.iter().find(|m| m.name == "Flying Press").unwrap();
+        assert_eq!(
+            flying_press.effectiveness(&Pokemon::from((Fighting, Grass))),
+            4.0
+        );
+    }
+
+    #[test]
+    fn test_type_changes() {
+        use BasicType::*;
+        let skarmory = Pokemon::from((Steel, Flying));
+        let roosted = skarmory.with_type_changes([TypeChange::Roost]);
+        assert_eq!(roosted.typing, Typing::from(Steel));
+        assert_eq!(roosted.defense().get(Ground), 2.0);
+        let tornadus =
+            Pokemon::from(Flying).with_type_changes([TypeChange::of_move("Roost").unwrap()]);
+        assert_eq!(tornadus.typing, Typing::from(Normal));
+        // Typeless
+        let burnt =
+            Pokemon::from(Fire).with_type_changes([TypeChange::of_move("Burn Up").unwrap()]);
+        assert_eq!(burnt.defense(), Relationship::neutral());
+        assert!(burnt.attacking_types().is_empty());
+        // Third types, where a later one replaces the earlier
+        let cursed = skarmory.with_type_changes([TypeChange::of_move("Forests Curse").unwrap()]);
+        assert_eq!(cursed.defense().get(Fire), 4.0);
+        let haunted = skarmory.with_type_changes([
+            TypeChange::of_move("Forest's Curse").unwrap(),
+            TypeChange::of_move("Trick Or Treat").unwrap(),
+        ]);
+        assert_eq!(
+            haunted.typing.iter().copied().collect::<Vec<_>>(),
+            vec![Flying, Ghost, Steel]
+        );
+        let soaked = skarmory.with_type_changes([TypeChange::of_move("Soak").unwrap()]);
+        assert_eq!(soaked.defense(), Water.defense());
+        // Tera types stay
+        let tera = Pokemon {
+            tera: Some(TeraType::Basic(Fire)),
+            ..skarmory.clone()
+        };
+        assert_eq!(
+            tera.with_type_changes([TypeChange::Replace(Water)])
+                .defense(),
+            Fire.defense()
+        );
+    }
+
+    #[test]
+    fn test_tera() {
+        use BasicType::*;
+        let dragonite = Pokemon::from((Dragon, Flying));
+        let tera_normal = Pokemon {
+            tera: Some(TeraType::Basic(Normal)),
+            ..dragonite.clone()
+        };
+        assert_eq!(dragonite.defense().get(Ice), 4.0);
+        assert_eq!(tera_normal.defense().get(Ice), 1.0);
+        assert_eq!(tera_normal.defense().get(Ghost), 0.0);
+        // Original STABs are kept
+        assert_eq!(
+            tera_normal.stabs(),
+            BTreeSet::from([Normal, Dragon, Flying])
+        );
+        let stellar = Pokemon {
+            tera: Some(TeraType::Stellar),
+            ..dragonite.clone()
+        };
+        assert_eq!(stellar.defense(), dragonite.defense());
+        assert_eq!(stellar.attacking_types(), vec![Flying, Dragon]);
+        // Untera'd Pokemon serialize as before
+        let json = serde_json::to_string(&dragonite).unwrap();
+        assert!(!json.contains("tera"));
+        assert_eq!(serde_json::from_str::<Pokemon>(&json).unwrap(), dragonite);
     }
 }
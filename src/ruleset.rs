@@ -0,0 +1,276 @@
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use strum::{Display, EnumCount, EnumIter, EnumString};
+
+use crate::{
+    field::Field,
+    pokemon::Pokemon,
+    typing::{AttackTrait, BasicType, Multiplier, Relationship, TeraType, TypeChart, TypeTrait},
+};
+
+/// Games that share a type chart and set of types
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    EnumIter,
+    EnumCount,
+    Ord,
+    PartialOrd,
+    EnumString,
+    Display,
+    Serialize,
+    Deserialize,
+)]
+pub enum Generation {
+    /// Red, Blue and Yellow: no Dark or Steel, Ghost does not affect Psychic
+    #[strum(serialize = "gen1")]
+    Gen1,
+    /// Gold through Black 2: no Fairy, Steel resists Ghost and Dark
+    #[strum(serialize = "gen2-5")]
+    Gen2To5,
+    #[strum(serialize = "gen6+")]
+    #[default]
+    Gen6Plus,
+}
+
+impl Generation {
+    pub fn chart(&self) -> &'static TypeChart {
+        static CHARTS: [OnceLock<TypeChart>; Generation::COUNT] =
+            [const { OnceLock::new() }; Generation::COUNT];
+        CHARTS[*self as usize].get_or_init(|| {
+            let json = match self {
+                Generation::Gen1 => include_str!("../data/type_chart_gen1.json"),
+                Generation::Gen2To5 => include_str!("../data/type_chart_gen2-5.json"),
+                Generation::Gen6Plus => include_str!("../data/type_chart_gen6+.json"),
+            };
+            TypeChart::from_json(json).unwrap()
+        })
+    }
+
+    /// Last game generation covered, `None` for the latest games
+    pub fn last_game(&self) -> Option<u8> {
+        match self {
+            Generation::Gen1 => Some(1),
+            Generation::Gen2To5 => Some(5),
+            Generation::Gen6Plus => None,
+        }
+    }
+
+    /// Whether Pokemon in this generation have abilities
+    pub fn has_abilities(&self) -> bool {
+        *self != Generation::Gen1
+    }
+}
+
+/// The generation, type chart and field conditions that matchups are evaluated under
+#[derive(Clone, Debug)]
+pub struct Ruleset {
+    generation: Generation,
+    chart: TypeChart,
+    field: Field,
+    /// `chart` with the field's per-type changes
+    field_chart: TypeChart,
+    /// `field_chart` with Ghost immunities lifted, for attackers like Scrappy
+    identified: TypeChart,
+    opponents: OnceLock<Vec<Profile<'static>>>,
+}
+
+impl Ruleset {
+    pub fn new(generation: Generation) -> Ruleset {
+        Ruleset::build(generation, generation.chart().clone(), Field::default())
+    }
+
+    fn build(generation: Generation, chart: TypeChart, field: Field) -> Ruleset {
+        let field_chart = field.chart(&chart);
+        Ruleset {
+            generation,
+            chart,
+            field,
+            identified: field_chart.identified(),
+            field_chart,
+            opponents: OnceLock::new(),
+        }
+    }
+
+    /// Replace the generation's chart, e.g. with one loaded for a ROM hack
+    pub fn with_chart(self, chart: TypeChart) -> Ruleset {
+        Ruleset::build(self.generation, chart, self.field)
+    }
+
+    /// Evaluate matchups under weather, terrain or Gravity
+    pub fn with_field(self, field: Field) -> Ruleset {
+        Ruleset::build(self.generation, self.chart, field)
+    }
+
+    /// Inverse Battle rules on top of the current chart
+    pub fn inverse(self) -> Ruleset {
+        let chart = self.chart.inverse();
+        self.with_chart(chart)
+    }
+
+    pub fn generation(&self) -> Generation {
+        self.generation
+    }
+
+    /// Type chart before field conditions
+    pub fn chart(&self) -> &TypeChart {
+        &self.chart
+    }
+
+    pub fn field(&self) -> &Field {
+        &self.field
+    }
+
+    /// Type chart after the field's per-type changes
+    pub fn field_chart(&self) -> &TypeChart {
+        &self.field_chart
+    }
+
+    /// Attacking types that exist under this ruleset
+    pub fn types(&self) -> impl Iterator<Item = BasicType> + '_ {
+        self.chart.types()
+    }
+
+    /// Damage taken by type including field conditions, which weather and terrain scale
+    /// apart from type effectiveness
+    pub fn defense(&self, pokemon: &impl TypeTrait) -> Relationship {
+        self.field.damage_taken(
+            pokemon.defense_with(&self.field_chart),
+            pokemon.is_grounded(),
+        )
+    }
+
+    /// Type effectiveness against the Pokemon under the field's chart, without the damage
+    /// modifiers of weather and terrain
+    pub fn profile<'a>(&self, pokemon: &'a Pokemon) -> Profile<'a> {
+        let defense = pokemon.defense_with(&self.field_chart);
+        let identified = if pokemon.defensive_typing().contains(BasicType::Ghost) {
+            pokemon.defense_with(&self.identified)
+        } else {
+            defense
+        };
+        Profile {
+            pokemon,
+            defense,
+            identified,
+        }
+    }
+
+    /// Tera types available under this ruleset
+    pub fn tera_types(&self) -> Vec<TeraType> {
+        self.types()
+            .map(TeraType::Basic)
+            .chain([TeraType::Stellar])
+            .collect()
+    }
+
+    /// Every distinct type chart entry of the generation, profiled once per ruleset
+    pub fn opponents(&self) -> &[Profile<'static>] {
+        self.opponents.get_or_init(|| {
+            Pokemon::all_unique_type_chart(self.generation)
+                .iter()
+                .map(|p| self.profile(p))
+                .collect()
+        })
+    }
+}
+
+impl Default for Ruleset {
+    fn default() -> Ruleset {
+        Ruleset::new(Generation::default())
+    }
+}
+
+/// A Pokemon with its defensive relationship computed once under a ruleset
+#[derive(Clone, Debug, PartialEq)]
+pub struct Profile<'a> {
+    pub pokemon: &'a Pokemon,
+    pub defense: Relationship,
+    /// Defense against attackers that ignore Ghost immunities
+    pub identified: Relationship,
+}
+
+impl Profile<'_> {
+    /// Best multiplier the attacker hits this Pokemon with
+    pub fn taken_from(&self, attacker: &impl AttackTrait) -> Multiplier {
+        attacker.effectiveness_against_identified(&self.defense, &self.identified)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::Weather;
+    use BasicType::*;
+
+    #[test]
+    fn test_gen1_chart() {
+        let chart = Generation::Gen1.chart();
+        assert_eq!(chart.types().count(), 15);
+        assert_eq!(Psychic.defense_with(chart).get(Ghost), 0.0);
+        assert_eq!(Poison.defense_with(chart).get(Bug), 2.0);
+        assert_eq!(Bug.defense_with(chart).get(Poison), 2.0);
+        assert_eq!(Fire.defense_with(chart).get(Ice), 1.0);
+    }
+
+    #[test]
+    fn test_gen2_to_5_chart() {
+        let chart = Generation::Gen2To5.chart();
+        assert_eq!(chart.types().count(), 17);
+        assert_eq!(Steel.defense_with(chart).get(Ghost), 0.5);
+        assert_eq!(Steel.defense_with(chart).get(Dark), 0.5);
+        assert_eq!(Psychic.defense_with(chart).get(Ghost), 2.0);
+    }
+
+    #[test]
+    fn test_gen1_pokemon() {
+        let all_pokemon = Pokemon::all(Generation::Gen1);
+        assert_eq!(all_pokemon.len(), 151);
+        let magnemite = all_pokemon
+            .iter()
+            .find(|p| p.species == "Magnemite")
+            .unwrap();
+        assert_eq!(magnemite.typing, Pokemon::from(Electric).typing);
+        assert!(all_pokemon.iter().all(|p| p.ability.is_none()));
+    }
+
+    #[test]
+    fn test_field() {
+        let rain = Field {
+            weather: Some(Weather::Rain),
+            ..Field::default()
+        };
+        let rules = Ruleset::default().with_field(rain);
+        let heatran = Pokemon::from((Fire, Steel));
+        assert_eq!(rules.defense(&heatran).get(Water), 3.0);
+        // Weather scales damage, not effectiveness
+        assert_eq!(rules.profile(&heatran).defense.get(Fire), 1.0);
+        assert_eq!(rules.profile(&heatran).defense.get(Water), 2.0);
+        assert_eq!(
+            rules.defense(&heatran),
+            heatran.defense_in(rules.chart(), &rain)
+        );
+        // The chart can still be swapped afterwards
+        let inverse = rules.inverse();
+        assert_eq!(inverse.field(), &rain);
+        assert_eq!(inverse.defense(&heatran).get(Water), 0.75);
+    }
+
+    #[test]
+    fn test_opponents() {
+        let rules = Ruleset::new(Generation::Gen1);
+        let opponents = rules.opponents();
+        assert_eq!(
+            opponents.len(),
+            Pokemon::all_unique_type_chart(Generation::Gen1).len()
+        );
+        assert!(opponents
+            .iter()
+            .all(|p| p.defense == p.pokemon.defense_with(rules.chart())));
+    }
+}
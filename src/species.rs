@@ -0,0 +1,393 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    str::FromStr,
+    sync::{Arc, OnceLock},
+};
+use strum::{EnumCount, EnumString};
+
+use crate::{ruleset::Generation, stats::Stats, typing::BasicType};
+
+/// Species and forms keyed by species name and form index
+type SpeciesByForm = BTreeMap<(String, u8), Arc<Species>>;
+
+/// How a form differs from the species' base form
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumString, Serialize, Deserialize,
+)]
+#[strum(serialize_all = "lowercase")]
+pub enum FormKind {
+    Base,
+    /// Alolan, Galarian, Hisuian and Paldean forms
+    Regional,
+    Mega,
+    Primal,
+    /// Other forms with their own typing, abilities or stats, e.g. Rotom's appliances
+    Alternate,
+}
+
+impl FormKind {
+    /// Whether the form is only taken during a battle, like Megas
+    pub fn is_battle_only(&self) -> bool {
+        matches!(self, FormKind::Mega | FormKind::Primal)
+    }
+}
+
+/// Everything the Pokedex files know about a species or one of its forms
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Species {
+    pub dex: u16,
+    pub name: String,
+    /// Index of the form as the games number it, 0 for the base form
+    pub form: u8,
+    /// e.g. "Alolan Vulpix", `None` for the base form
+    pub form_name: Option<String>,
+    pub form_kind: FormKind,
+    /// Generation the species or form was introduced in
+    pub generation: u8,
+    /// Types in the latest games, including those the generation's chart lacks
+    pub types: Vec<BasicType>,
+    /// e.g. "Seed Pokémon"
+    pub category: String,
+    /// In decimetres
+    pub height: u16,
+    /// In hectograms
+    pub weight: u16,
+    /// Regular abilities followed by the hidden ability, by name
+    pub abilities: Vec<String>,
+    pub base_stats: Stats,
+    pub ev_yield: Stats,
+    pub catch_rate: u8,
+    /// `None` where the data files have no value, as for some gen 9 species
+    pub base_friendship: Option<u8>,
+    pub base_exp: Option<u16>,
+    pub growth_rate: String,
+    pub egg_groups: Vec<String>,
+    /// Eighths of the species that are female, `None` if genderless
+    pub female_eighths: Option<u8>,
+    pub egg_cycles: Option<u8>,
+    /// "Ordinary", "Legendary", "Mythical", "Baby Pokemon" and so on
+    pub special_group: String,
+}
+
+impl Species {
+    /// Every species and form in the games of the generation, each form following its base form
+    pub fn all(generation: Generation) -> &'static Vec<Arc<Species>> {
+        static CELLS: [OnceLock<Vec<Arc<Species>>>; Generation::COUNT] =
+            [const { OnceLock::new() }; Generation::COUNT];
+        CELLS[generation as usize].get_or_init(|| {
+            let file = match generation {
+                Generation::Gen1 | Generation::Gen2To5 => "data/pokemon_data_gen5.csv",
+                Generation::Gen6Plus => "data/pokemon_data_gen6+.csv",
+            };
+            let base = csv::Reader::from_path(file)
+                .unwrap()
+                .into_records()
+                .map(|r| Species::from_record(&r.unwrap()))
+                .filter(|s| in_games(generation, s.generation))
+                .collect::<Vec<_>>();
+            let mut forms = csv::Reader::from_path("data/pokemon_forms.csv")
+                .unwrap()
+                .into_records()
+                .map(|r| r.unwrap())
+                .filter(|record| in_games(generation, record.get(4).unwrap().parse().unwrap()))
+                .map(|record| {
+                    let species = base
+                        .iter()
+                        .find(|s| s.name == record.get(0).unwrap())
+                        .unwrap_or_else(|| panic!("No base form for {:?}", record.get(2)));
+                    Species::from_form_record(&record, species)
+                })
+                .collect::<Vec<_>>();
+            base.into_iter()
+                .flat_map(|species| {
+                    let name = species.name.clone();
+                    let mut own = vec![species];
+                    own.extend(forms.extract_if(.., |f| f.name == name));
+                    own.into_iter().map(Arc::new)
+                })
+                .collect()
+        })
+    }
+
+    /// The species' form by index, `None` if it is not in the games of the generation
+    pub fn get(generation: Generation, name: &str, form: u8) -> Option<&'static Arc<Species>> {
+        static CELLS: [OnceLock<SpeciesByForm>; Generation::COUNT] =
+            [const { OnceLock::new() }; Generation::COUNT];
+        CELLS[generation as usize]
+            .get_or_init(|| {
+                Species::all(generation)
+                    .iter()
+                    .map(|s| ((s.name.clone(), s.form), s.clone()))
+                    .collect()
+            })
+            .get(&(name.to_string(), form))
+    }
+
+    /// Name of the form, or of the species for the base form
+    pub fn display_name(&self) -> &str {
+        self.form_name.as_deref().unwrap_or(&self.name)
+    }
+
+    /// Parses a row of the `pokemon_data_*.csv` files
+    pub fn from_record(record: &csv::StringRecord) -> Species {
+        // dexnum,name,generation,type1,type2,species,height,weight,ability1,ability2,hidden_ability,hp,attack,defense,sp_atk,sp_def,speed,total,ev_yield,catch_rate,base_friendship,base_exp,growth_rate,egg_group1,egg_group2,percent_male,percent_female,egg_cycles,special_group
+        let field = |i: usize| record.get(i).unwrap_or_default().trim();
+        let number = |i: usize| {
+            field(i)
+                .parse()
+                .unwrap_or_else(|_| panic!("Invalid number {:?} for {:?}", field(i), field(1)))
+        };
+        // Written as "—" when unknown
+        let optional = |i: usize| match field(i) {
+            "—" | "" => None,
+            _ => Some(number(i)),
+        };
+        // Written in metres and kilograms with one decimal
+        let tenths = |i: usize| {
+            let value: f32 = field(i)
+                .parse()
+                .unwrap_or_else(|_| panic!("Invalid number {:?} for {:?}", field(i), field(1)));
+            (value * 10.0).round() as u16
+        };
+        let species = Species {
+            dex: number(0),
+            name: field(1).to_string(),
+            form: 0,
+            form_name: None,
+            form_kind: FormKind::Base,
+            generation: number(2) as u8,
+            types: [3, 4]
+                .into_iter()
+                .map(field)
+                .filter(|t| !t.is_empty())
+                .map(|t| BasicType::from_str(t).unwrap())
+                .collect(),
+            category: field(5).to_string(),
+            height: tenths(6),
+            weight: tenths(7),
+            abilities: [8, 9, 10]
+                .into_iter()
+                .map(field)
+                .filter(|a| !a.is_empty())
+                .map(String::from)
+                .collect(),
+            base_stats: Stats {
+                hp: number(11),
+                attack: number(12),
+                defense: number(13),
+                special_attack: number(14),
+                special_defense: number(15),
+                speed: number(16),
+            },
+            ev_yield: ev_yield(field(18)),
+            catch_rate: number(19) as u8,
+            base_friendship: optional(20).map(|f| f as u8),
+            base_exp: optional(21),
+            growth_rate: field(22).to_string(),
+            egg_groups: [23, 24]
+                .into_iter()
+                .map(field)
+                .filter(|g| !g.is_empty())
+                .map(String::from)
+                .collect(),
+            female_eighths: match field(26) {
+                "" => None,
+                percent => Some((percent.parse::<f32>().unwrap() * 8.0 / 100.0).round() as u8),
+            },
+            egg_cycles: optional(27).map(|c| c as u8),
+            special_group: field(28).to_string(),
+        };
+        assert_eq!(
+            species.base_stats.total(),
+            number(17),
+            "Base stats of {:?} do not add up",
+            species.name
+        );
+        species
+    }
+
+    /// Parses a row of `pokemon_forms.csv`. Blank types, abilities and stats are the base form's
+    pub fn from_form_record(record: &csv::StringRecord, base: &Species) -> Species {
+        // name,form,form_name,kind,generation,type1,type2,ability1,ability2,hidden_ability,hp,attack,defense,sp_atk,sp_def,speed
+        let field = |i: usize| record.get(i).unwrap_or_default().trim();
+        let number = |i: usize| {
+            field(i)
+                .parse()
+                .unwrap_or_else(|_| panic!("Invalid number {:?} for {:?}", field(i), field(2)))
+        };
+        let types = match field(5) {
+            "" => base.types.clone(),
+            _ => [5, 6]
+                .into_iter()
+                .map(field)
+                .filter(|t| !t.is_empty())
+                .map(|t| BasicType::from_str(t).unwrap())
+                .collect(),
+        };
+        let abilities = match field(7) {
+            "" => base.abilities.clone(),
+            _ => [7, 8, 9]
+                .into_iter()
+                .map(field)
+                .filter(|a| !a.is_empty())
+                .map(String::from)
+                .collect(),
+        };
+        let base_stats = match field(10) {
+            "" => base.base_stats,
+            _ => Stats {
+                hp: number(10),
+                attack: number(11),
+                defense: number(12),
+                special_attack: number(13),
+                special_defense: number(14),
+                speed: number(15),
+            },
+        };
+        Species {
+            form: number(1) as u8,
+            form_name: Some(field(2).to_string()),
+            form_kind: FormKind::from_str(field(3))
+                .unwrap_or_else(|_| panic!("Unknown form kind {:?}", field(3))),
+            generation: number(4) as u8,
+            types,
+            abilities,
+            base_stats,
+            ..base.clone()
+        }
+    }
+}
+
+/// Whether something introduced in generation `introduced` is in the games of `generation`
+fn in_games(generation: Generation, introduced: u8) -> bool {
+    generation.last_game().is_none_or(|last| introduced <= last)
+}
+
+/// Parses EV yields like "1 Sp. Atk, 1 Sp. Def"
+fn ev_yield(s: &str) -> Stats {
+    let mut stats = Stats::default();
+    for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (amount, stat) = part
+            .split_once(' ')
+            .unwrap_or_else(|| panic!("Invalid EV yield {s:?}"));
+        let amount = amount.parse().unwrap();
+        match stat {
+            "HP" => stats.hp = amount,
+            "Attack" => stats.attack = amount,
+            "Defense" => stats.defense = amount,
+            "Sp. Atk" => stats.special_attack = amount,
+            "Sp. Def" => stats.special_defense = amount,
+            "Speed" => stats.speed = amount,
+            _ => panic!("Unknown stat {stat:?} in EV yield {s:?}"),
+        }
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pokemon::Pokemon, ruleset::Generation};
+    use std::collections::BTreeSet;
+
+    fn species(name: &str, generation: Generation) -> &'static Species {
+        Pokemon::all(generation)
+            .iter()
+            .find(|p| p.species == name)
+            .and_then(|p| p.species_data.as_deref())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_species() {
+        let bulbasaur = species("Bulbasaur", Generation::Gen6Plus);
+        assert_eq!(bulbasaur.dex, 1);
+        assert_eq!(bulbasaur.generation, 1);
+        assert_eq!(bulbasaur.base_stats.total(), 318);
+        assert_eq!(bulbasaur.base_stats.speed, 45);
+        assert_eq!(bulbasaur.ev_yield.special_attack, 1);
+        assert_eq!(bulbasaur.height, 7);
+        assert_eq!(bulbasaur.weight, 69);
+        assert_eq!(bulbasaur.egg_groups, vec!["Grass", "Monster"]);
+        assert_eq!(bulbasaur.female_eighths, Some(1));
+        assert_eq!(bulbasaur.abilities, vec!["Overgrow", "Chlorophyll"]);
+        let magnemite = species("Magnemite", Generation::Gen6Plus);
+        assert_eq!(magnemite.female_eighths, None);
+    }
+
+    #[test]
+    fn test_filters() {
+        let pokemon = Pokemon::all(Generation::Gen6Plus);
+        assert!(pokemon.iter().all(|p| p.species_data.is_some()));
+        let old = pokemon
+            .iter()
+            .filter(|p| p.species_data.as_ref().is_some_and(|s| s.generation <= 5))
+            .count();
+        assert!(old > 0 && old < pokemon.len());
+        assert!(pokemon.iter().filter(|p| p.species == "Garchomp").all(|p| p
+            .species_data
+            .as_ref()
+            .unwrap()
+            .base_stats
+            .total()
+            >= 500));
+        // Appliance forms are stronger than base Rotom
+        let rotom = pokemon
+            .iter()
+            .filter(|p| p.species == "Rotom")
+            .map(|p| p.species_data.as_ref().unwrap().base_stats.total())
+            .collect::<BTreeSet<_>>();
+        assert_eq!(rotom, BTreeSet::from([440, 520]));
+    }
+
+    #[test]
+    fn test_forms() {
+        use BasicType::*;
+        let vulpix = Species::get(Generation::Gen6Plus, "Vulpix", 1).unwrap();
+        assert_eq!(vulpix.display_name(), "Alolan Vulpix");
+        assert_eq!(vulpix.form_kind, FormKind::Regional);
+        assert_eq!(vulpix.types, vec![Ice]);
+        assert_eq!(vulpix.abilities, vec!["Snow Cloak", "Snow Warning"]);
+        // Inherited from the base form
+        assert_eq!(vulpix.dex, 37);
+        assert_eq!(vulpix.base_stats.total(), 299);
+
+        let charizard = Species::get(Generation::Gen6Plus, "Charizard", 1).unwrap();
+        assert_eq!(charizard.display_name(), "Mega Charizard X");
+        assert_eq!(charizard.types, vec![Fire, Dragon]);
+        assert!(charizard.form_kind.is_battle_only());
+        assert_eq!(charizard.base_stats.total(), 634);
+        assert_eq!(
+            Species::get(Generation::Gen6Plus, "Arceus", 17)
+                .unwrap()
+                .types,
+            vec![Fairy]
+        );
+        assert_eq!(
+            Species::get(Generation::Gen6Plus, "Wormadam", 2)
+                .unwrap()
+                .types,
+            vec![Bug, Steel]
+        );
+
+        // Forms from later games are left out
+        assert!(Species::get(Generation::Gen2To5, "Charizard", 1).is_none());
+        assert!(Species::get(Generation::Gen2To5, "Arceus", 17).is_none());
+        assert!(Species::get(Generation::Gen2To5, "Arceus", 16).is_some());
+        assert!(Species::get(Generation::Gen1, "Vulpix", 1).is_none());
+        assert!(Species::all(Generation::Gen1)
+            .iter()
+            .all(|s| s.generation == 1));
+
+        // Megas are not Pokemon outside of battle
+        let pokemon = Pokemon::all(Generation::Gen6Plus);
+        assert!(pokemon.iter().all(|p| !p
+            .species_data
+            .as_ref()
+            .unwrap()
+            .form_kind
+            .is_battle_only()));
+        assert!(pokemon.iter().any(|p| p.name() == "Alolan Vulpix"));
+    }
+}
@@ -0,0 +1,246 @@
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter, EnumString, IntoEnumIterator};
+
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, EnumIter, Ord, PartialOrd, Serialize, Deserialize,
+)]
+pub enum Stat {
+    Hp,
+    Attack,
+    Defense,
+    SpecialAttack,
+    SpecialDefense,
+    Speed,
+}
+
+/// HP, Attack, Defense, Sp. Atk, Sp. Def and Speed
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub struct Stats {
+    pub hp: u16,
+    pub attack: u16,
+    pub defense: u16,
+    pub special_attack: u16,
+    pub special_defense: u16,
+    pub speed: u16,
+}
+
+impl Stats {
+    pub fn total(&self) -> u16 {
+        self.hp
+            + self.attack
+            + self.defense
+            + self.special_attack
+            + self.special_defense
+            + self.speed
+    }
+
+    pub fn get(&self, stat: Stat) -> u16 {
+        match stat {
+            Stat::Hp => self.hp,
+            Stat::Attack => self.attack,
+            Stat::Defense => self.defense,
+            Stat::SpecialAttack => self.special_attack,
+            Stat::SpecialDefense => self.special_defense,
+            Stat::Speed => self.speed,
+        }
+    }
+
+    /// Stats with each value given by `f`
+    pub fn from_fn(f: impl Fn(Stat) -> u16) -> Stats {
+        Stats {
+            hp: f(Stat::Hp),
+            attack: f(Stat::Attack),
+            defense: f(Stat::Defense),
+            special_attack: f(Stat::SpecialAttack),
+            special_defense: f(Stat::SpecialDefense),
+            speed: f(Stat::Speed),
+        }
+    }
+
+    /// Stats that differ from `other`
+    pub fn differences(&self, other: &Stats) -> Vec<Stat> {
+        Stat::iter()
+            .filter(|s| self.get(*s) != other.get(*s))
+            .collect()
+    }
+}
+
+/// Natures in their in-game index order
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    EnumIter,
+    Ord,
+    PartialOrd,
+    EnumString,
+    Display,
+    Serialize,
+    Deserialize,
+)]
+pub enum Nature {
+    Hardy,
+    Lonely,
+    Brave,
+    Adamant,
+    Naughty,
+    Bold,
+    Docile,
+    Relaxed,
+    Impish,
+    Lax,
+    Timid,
+    Hasty,
+    Serious,
+    Jolly,
+    Naive,
+    Modest,
+    Mild,
+    Quiet,
+    Bashful,
+    Rash,
+    Calm,
+    Gentle,
+    Sassy,
+    Careful,
+    Quirky,
+}
+
+impl Nature {
+    // The index encodes the raised stat in base 5 followed by the lowered one
+    const ORDER: [Stat; 5] = [
+        Stat::Attack,
+        Stat::Defense,
+        Stat::Speed,
+        Stat::SpecialAttack,
+        Stat::SpecialDefense,
+    ];
+
+    /// Stat raised by 10%, `None` for neutral natures
+    pub fn increased(&self) -> Option<Stat> {
+        let index = *self as usize;
+        (index / 5 != index % 5).then(|| Nature::ORDER[index / 5])
+    }
+
+    /// Stat lowered by 10%, `None` for neutral natures
+    pub fn decreased(&self) -> Option<Stat> {
+        let index = *self as usize;
+        (index / 5 != index % 5).then(|| Nature::ORDER[index % 5])
+    }
+}
+
+/// What sets one Pokemon's stats apart from others of its species
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Individual {
+    pub level: u8,
+    pub nature: Nature,
+    pub ivs: Stats,
+    pub evs: Stats,
+    /// Actual stats, as computed by `Individual::new` or read from a save
+    pub stats: Stats,
+    /// Stats of a save that the formula gets wrong, e.g. when the game's base stats differ
+    /// from the data files
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mismatched: Vec<Stat>,
+}
+
+impl Individual {
+    pub fn new(
+        level: u8,
+        nature: Nature,
+        ivs: Stats,
+        evs: Stats,
+        base_stats: &Stats,
+    ) -> Individual {
+        let mut individual = Individual {
+            level,
+            nature,
+            ivs,
+            evs,
+            stats: Stats::default(),
+            mismatched: vec![],
+        };
+        individual.stats = individual.calculate(base_stats);
+        individual
+    }
+
+    /// Stats from the gen 3 onwards formula
+    pub fn calculate(&self, base_stats: &Stats) -> Stats {
+        let level = self.level as u32;
+        Stats::from_fn(|stat| {
+            let base = base_stats.get(stat) as u32;
+            let core = (2 * base + self.ivs.get(stat) as u32 + self.evs.get(stat) as u32 / 4)
+                * level
+                / 100;
+            let value = match stat {
+                // Shedinja always has 1 HP
+                Stat::Hp if base == 1 => 1,
+                Stat::Hp => core + level + 10,
+                _ if self.nature.increased() == Some(stat) => (core + 5) * 11 / 10,
+                _ if self.nature.decreased() == Some(stat) => (core + 5) * 9 / 10,
+                _ => core + 5,
+            };
+            value as u16
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(values: [u16; 6]) -> Stats {
+        let [hp, attack, defense, special_attack, special_defense, speed] = values;
+        Stats {
+            hp,
+            attack,
+            defense,
+            special_attack,
+            special_defense,
+            speed,
+        }
+    }
+
+    #[test]
+    fn test_natures() {
+        assert_eq!(Nature::Adamant.increased(), Some(Stat::Attack));
+        assert_eq!(Nature::Adamant.decreased(), Some(Stat::SpecialAttack));
+        assert_eq!(Nature::Timid.increased(), Some(Stat::Speed));
+        assert_eq!(Nature::Timid.decreased(), Some(Stat::Attack));
+        assert_eq!(Nature::Serious.increased(), None);
+        assert_eq!(
+            Nature::iter().filter(|n| n.increased().is_none()).count(),
+            5
+        );
+    }
+
+    #[test]
+    fn test_calculate() {
+        // Bulbapedia's worked example
+        let garchomp = Individual::new(
+            78,
+            Nature::Adamant,
+            stats([24, 12, 30, 16, 23, 5]),
+            stats([74, 190, 91, 48, 84, 23]),
+            &stats([108, 130, 95, 80, 85, 102]),
+        );
+        assert_eq!(garchomp.stats, stats([289, 278, 193, 135, 171, 171]));
+        let shedinja = Individual::new(
+            50,
+            Nature::Hardy,
+            stats([31; 6]),
+            stats([0; 6]),
+            &stats([1, 90, 45, 30, 30, 40]),
+        );
+        assert_eq!(shedinja.stats.hp, 1);
+        assert_eq!(
+            stats([1, 2, 3, 4, 5, 6]).differences(&stats([1, 2, 0, 4, 5, 0])),
+            vec![Stat::Defense, Stat::Speed]
+        );
+    }
+}
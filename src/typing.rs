@@ -1,7 +1,15 @@
+pub mod multiplier;
+
+use crate::field::Field;
+use crate::item::Item;
+use crate::ruleset::Generation;
+pub use multiplier::{InvalidMultiplier, Multiplier};
 use serde::Deserialize;
 use serde::Serialize;
 use std::collections::BTreeMap;
-use std::sync::OnceLock;
+use std::fmt;
+use std::path::Path;
+use strum::EnumCount;
 use strum::EnumIter;
 use strum::EnumString;
 use strum::IntoEnumIterator;
@@ -17,6 +25,7 @@ use strum::IntoEnumIterator;
     Ord,
     PartialOrd,
     EnumString,
+    EnumCount,
     Serialize,
     Deserialize,
 )]
@@ -38,7 +47,6 @@ pub enum BasicType {
     Dragon,
     Dark,
     Steel,
-    #[cfg(feature = "gen6")]
     Fairy,
 }
 
@@ -79,6 +87,255 @@ pub enum Ability {
     MotorDrive,
     #[strum(serialize = "Storm Drain")]
     StormDrain,
+    #[strum(serialize = "Fluffy")]
+    Fluffy,
+    #[strum(serialize = "Water Bubble")]
+    WaterBubble,
+    #[strum(serialize = "Purifying Salt")]
+    PurifyingSalt,
+    #[strum(serialize = "Earth Eater")]
+    EarthEater,
+    #[strum(serialize = "Well-Baked Body")]
+    WellBakedBody,
+    /// Only super effective hits land
+    #[strum(serialize = "Wonder Guard")]
+    WonderGuard,
+    /// Super effective hits deal 3/4 damage
+    #[strum(serialize = "Filter")]
+    Filter,
+    #[strum(serialize = "Solid Rock")]
+    SolidRock,
+    #[strum(serialize = "Prism Armor")]
+    PrismArmor,
+    /// Normal and Fighting hit Ghosts
+    #[strum(serialize = "Scrappy")]
+    Scrappy,
+    #[strum(serialize = "Mind's Eye")]
+    MindsEye,
+    /// Immune to wind moves, see `SpecialMove::Wind`
+    #[strum(serialize = "Wind Rider")]
+    WindRider,
+    /// Not very effective hits deal double damage
+    #[strum(serialize = "Tinted Lens")]
+    TintedLens,
+    #[strum(serialize = "Pixilate")]
+    Pixilate,
+    #[strum(serialize = "Aerilate")]
+    Aerilate,
+    #[strum(serialize = "Refrigerate")]
+    Refrigerate,
+    #[strum(serialize = "Galvanize")]
+    Galvanize,
+    /// Every move becomes Normal
+    #[strum(serialize = "Normalize")]
+    Normalize,
+    /// STAB is 2x instead of 1.5x
+    #[strum(serialize = "Adaptability")]
+    Adaptability,
+}
+
+impl Ability {
+    /// Defense of a Pokemon with this ability given the relationship of its typing.
+    /// Chart entries multiply in, abilities that depend on the type matchup itself are applied on top.
+    pub fn apply_defense(&self, typing: Relationship, chart: &TypeChart) -> Relationship {
+        let defense = combine_defense_charts([typing, self.defense_with(chart)]);
+        match self {
+            Ability::WonderGuard => defense.map(|t, m| {
+                if typing.get(t) > Multiplier::NEUTRAL {
+                    m
+                } else {
+                    Multiplier::IMMUNE
+                }
+            }),
+            Ability::Filter | Ability::SolidRock | Ability::PrismArmor => defense.map(|t, m| {
+                if typing.get(t) > Multiplier::NEUTRAL {
+                    m * Multiplier::new(3, 4)
+                } else {
+                    m
+                }
+            }),
+            _ => defense,
+        }
+    }
+
+    /// Whether this ability changes the hits of its holder
+    pub fn modifies_attack(&self) -> bool {
+        matches!(
+            self,
+            Ability::Scrappy
+                | Ability::MindsEye
+                | Ability::TintedLens
+                | Ability::Pixilate
+                | Ability::Aerilate
+                | Ability::Refrigerate
+                | Ability::Galvanize
+                | Ability::Normalize
+                | Ability::Adaptability
+        )
+    }
+
+    /// Type a move of type `t` is used as, e.g. Normal moves become Fairy with Pixilate
+    pub fn attack_type(&self, t: BasicType) -> BasicType {
+        match (self, t) {
+            (Ability::Normalize, _) => BasicType::Normal,
+            (Ability::Pixilate, BasicType::Normal) => BasicType::Fairy,
+            (Ability::Aerilate, BasicType::Normal) => BasicType::Flying,
+            (Ability::Refrigerate, BasicType::Normal) => BasicType::Ice,
+            (Ability::Galvanize, BasicType::Normal) => BasicType::Electric,
+            _ => t,
+        }
+    }
+
+    /// Power multiplier for a move of type `t`. Moves whose type the ability changes hit 1.2x,
+    /// as of gen 7
+    pub fn power_multiplier(&self, t: BasicType) -> Multiplier {
+        match (self, t) {
+            (Ability::Normalize, _)
+            | (
+                Ability::Pixilate | Ability::Aerilate | Ability::Refrigerate | Ability::Galvanize,
+                BasicType::Normal,
+            ) => Multiplier::new(6, 5),
+            _ => Multiplier::NEUTRAL,
+        }
+    }
+
+    /// Whether hits ignore the defender's Ghost type immunities, see `TypeChart::identified`
+    pub fn ignores_ghost_immunity(&self) -> bool {
+        matches!(self, Ability::Scrappy | Ability::MindsEye)
+    }
+
+    /// Effectiveness after the attacker's ability
+    pub fn modify_effectiveness(&self, m: Multiplier) -> Multiplier {
+        match self {
+            Ability::TintedLens if !m.is_immune() && m < Multiplier::NEUTRAL => {
+                m * Multiplier::SUPER_EFFECTIVE
+            }
+            _ => m,
+        }
+    }
+
+    /// Damage multiplier for same type attacks
+    pub fn stab_bonus(&self) -> Multiplier {
+        match self {
+            Ability::Adaptability => Multiplier::SUPER_EFFECTIVE,
+            _ => Multiplier::new(3, 2),
+        }
+    }
+}
+
+/// Moves whose effectiveness does not follow the chart entry for their type
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    EnumIter,
+    Ord,
+    PartialOrd,
+    EnumString,
+    Serialize,
+    Deserialize,
+)]
+pub enum SpecialMove {
+    /// Super effective on Water
+    #[strum(serialize = "Freeze-Dry", serialize = "Freeze Dry")]
+    FreezeDry,
+    /// Both Fighting and Flying
+    #[strum(serialize = "Flying Press")]
+    FlyingPress,
+    /// Neutral on ungrounded Flying types, hits through Levitate at its usual effectiveness
+    #[strum(serialize = "Thousand Arrows")]
+    ThousandArrows,
+    /// Fails on Flying types, as of gen 6
+    #[strum(serialize = "Sky Drop")]
+    SkyDrop,
+    /// Damaging wind moves, which Wind Rider is immune to
+    #[strum(
+        serialize = "Aeroblast",
+        serialize = "Air Cutter",
+        serialize = "Bleakwind Storm",
+        serialize = "Blizzard",
+        serialize = "Fairy Wind",
+        serialize = "Gust",
+        serialize = "Heat Wave",
+        serialize = "Hurricane",
+        serialize = "Icy Wind",
+        serialize = "Petal Blizzard",
+        serialize = "Sandsear Storm",
+        serialize = "Springtide Storm",
+        serialize = "Twister",
+        serialize = "Wildbolt Storm"
+    )]
+    Wind,
+}
+
+impl SpecialMove {
+    /// Chart that the move's type is looked up in
+    pub fn chart(&self, chart: &TypeChart) -> TypeChart {
+        use BasicType::*;
+        match self {
+            SpecialMove::FreezeDry => chart.clone().map_type(Water, |t, m| match t {
+                Ice => Multiplier::SUPER_EFFECTIVE,
+                _ => m,
+            }),
+            SpecialMove::SkyDrop => chart.clone().map_type(Flying, |t, m| match t {
+                Flying => Multiplier::IMMUNE,
+                _ => m,
+            }),
+            SpecialMove::ThousandArrows => chart.grounded(),
+            SpecialMove::Wind => {
+                let mut chart = chart.clone();
+                let immune = BasicType::iter().map(|t| (t, Multiplier::IMMUNE)).collect();
+                chart.abilities.insert(Ability::WindRider, immune);
+                chart
+            }
+            SpecialMove::FlyingPress => {
+                let mut chart = chart.clone();
+                for r in chart.types.values_mut().chain(chart.abilities.values_mut()) {
+                    r.inner[Fighting as usize] = r.get(Fighting) * r.get(Flying);
+                }
+                chart
+            }
+        }
+    }
+
+    /// Multiplier of this move used as type `t` against the defender
+    pub fn effectiveness_with(
+        &self,
+        t: BasicType,
+        defender: &impl TypeTrait,
+        chart: &TypeChart,
+    ) -> Multiplier {
+        let special_chart = self.chart(chart);
+        let m = defender.defense_with(&special_chart).get(t);
+        match self {
+            // Ungrounded Flying types are hit neutrally whatever their other type. Their Flying
+            // entry is the one that changes the result when replaced
+            SpecialMove::ThousandArrows
+                if !m.is_immune() && defender.defense_with(chart).get(t).is_immune() =>
+            {
+                let without_flying =
+                    special_chart.map_type(BasicType::Flying, |_, _| Multiplier::SUPER_EFFECTIVE);
+                if defender.defense_with(&without_flying).get(t) != m {
+                    Multiplier::NEUTRAL
+                } else {
+                    m
+                }
+            }
+            _ => m,
+        }
+    }
+}
+
+/// Type a Pokemon can Terastallize into
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
+pub enum TeraType {
+    /// Defends as this single type and gains it as a STAB
+    Basic(BasicType),
+    /// Keeps its original typing defensively
+    Stellar,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Ord, PartialOrd)]
@@ -90,364 +347,624 @@ pub enum Type {
 impl IntoEnumIterator for Type {
     type Iterator = std::vec::IntoIter<Self>;
 
+    /// Every defensive type, leaving out abilities that only change the holder's hits
     fn iter() -> Self::Iterator {
         BasicType::iter()
             .map(Type::Basic)
-            .chain(Ability::iter().map(Type::Ability))
+            .chain(
+                Ability::iter()
+                    .filter(|a| !a.modifies_attack())
+                    .map(Type::Ability),
+            )
             .collect::<Vec<_>>()
             .into_iter()
     }
 }
 
 impl TypeTrait for BasicType {
-    fn defense(&self) -> Relationship {
-        Relationship::from_raw_parts(
-            get_defense_chart()
-                .get(&Type::Basic(*self))
-                .unwrap()
-                .clone(),
-        )
+    fn defense_with(&self, chart: &TypeChart) -> Relationship {
+        chart.defense(&Type::Basic(*self))
+    }
+
+    fn is_grounded(&self) -> bool {
+        *self != BasicType::Flying
     }
 }
 
 impl TypeTrait for Ability {
-    fn defense(&self) -> Relationship {
-        Relationship::from_raw_parts(
-            get_defense_chart()
-                .get(&Type::Ability(*self))
-                .unwrap_or_else(|| panic!("{:?} not found", self))
-                .clone(),
-        )
+    fn defense_with(&self, chart: &TypeChart) -> Relationship {
+        chart.defense(&Type::Ability(*self))
+    }
+
+    fn is_grounded(&self) -> bool {
+        *self != Ability::Levitate
     }
 }
 
 pub trait TypeTrait {
-    fn defense(&self) -> Relationship;
+    /// Defensive relationship under the given type chart
+    fn defense_with(&self, chart: &TypeChart) -> Relationship;
+
+    /// Defensive relationship under the built-in type chart
+    fn defense(&self) -> Relationship {
+        self.defense_with(TypeChart::builtin())
+    }
+
+    /// Whether terrain applies to this, before Gravity
+    fn is_grounded(&self) -> bool {
+        true
+    }
+
+    /// Damage taken by type under the given type chart and field conditions,
+    /// see `Field::damage_taken`
+    fn defense_in(&self, chart: &TypeChart, field: &Field) -> Relationship {
+        field.damage_taken(self.defense_with(&field.chart(chart)), self.is_grounded())
+    }
+}
+
+/// Something that deals typed damage
+pub trait AttackTrait {
+    /// Types this attacks with, after any attacker-side modifiers
+    fn attacking_types(&self) -> Vec<BasicType>;
+
+    /// Ability of the attacker, if it changes its hits
+    fn attacker_ability(&self) -> Option<Ability> {
+        None
+    }
+
+    /// Held item of the attacker, if it changes its hits
+    fn attacker_item(&self) -> Option<Item> {
+        None
+    }
+
+    /// Best multiplier against a defender whose relationship is already computed.
+    /// Ghost immunities are not ignored, see `effectiveness_against_identified`
+    fn effectiveness_against(&self, defense: &Relationship) -> Multiplier {
+        self.effectiveness_against_identified(defense, defense)
+    }
+
+    /// Best multiplier against a defender, where `identified` is its relationship under
+    /// `TypeChart::identified` and is used by attackers that ignore Ghost immunities
+    fn effectiveness_against_identified(
+        &self,
+        defense: &Relationship,
+        identified: &Relationship,
+    ) -> Multiplier {
+        let ability = self.attacker_ability();
+        let item = self.attacker_item();
+        let defense = match ability {
+            Some(a) if a.ignores_ghost_immunity() => identified,
+            _ => defense,
+        };
+        self.attacking_types()
+            .into_iter()
+            .map(|t| {
+                let m = match ability {
+                    Some(a) => a.modify_effectiveness(defense.get(t)),
+                    None => defense.get(t),
+                };
+                match item {
+                    Some(i) => i.modify_effectiveness(m),
+                    None => m,
+                }
+            })
+            .fold(Multiplier::IMMUNE, Multiplier::max)
+    }
+
+    /// Best multiplier against the defender under the given type chart
+    fn effectiveness_with(&self, defender: &impl TypeTrait, chart: &TypeChart) -> Multiplier {
+        match self.attacker_ability() {
+            Some(a) if a.ignores_ghost_immunity() => {
+                self.effectiveness_against(&defender.defense_with(&chart.identified()))
+            }
+            _ => self.effectiveness_against(&defender.defense_with(chart)),
+        }
+    }
+
+    /// Best multiplier against the defender under the built-in type chart
+    fn effectiveness(&self, defender: &impl TypeTrait) -> Multiplier {
+        self.effectiveness_with(defender, TypeChart::builtin())
+    }
+
+    /// Offensive relationship against each single type under the given type chart
+    fn attack_with(&self, chart: &TypeChart) -> Relationship {
+        chart
+            .types()
+            .map(|t| (t, self.effectiveness_with(&t, chart)))
+            .collect()
+    }
+
+    /// Offensive relationship against each single type under the built-in type chart
+    fn attack(&self) -> Relationship {
+        self.attack_with(TypeChart::builtin())
+    }
+}
+
+impl AttackTrait for BasicType {
+    fn attacking_types(&self) -> Vec<BasicType> {
+        vec![*self]
+    }
+}
+
+/// A single attacking type together with the attacker's ability and item
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
+pub struct Attack {
+    pub typing: BasicType,
+    pub ability: Option<Ability>,
+    pub item: Option<Item>,
+}
+
+impl AttackTrait for Attack {
+    fn attacking_types(&self) -> Vec<BasicType> {
+        vec![self.typing]
+    }
+
+    fn attacker_ability(&self) -> Option<Ability> {
+        self.ability
+    }
+
+    fn attacker_item(&self) -> Option<Item> {
+        self.item
+    }
 }
 
 impl TypeTrait for Type {
-    fn defense(&self) -> Relationship {
+    fn defense_with(&self, chart: &TypeChart) -> Relationship {
         match self {
-            Type::Basic(t) => t.defense(),
-            Type::Ability(a) => a.defense(),
+            Type::Basic(t) => t.defense_with(chart),
+            Type::Ability(a) => a.defense_with(chart),
+        }
+    }
+
+    fn is_grounded(&self) -> bool {
+        match self {
+            Type::Basic(t) => t.is_grounded(),
+            Type::Ability(a) => a.is_grounded(),
         }
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+/// Multiplier taken from each attacking type, stored densely by `BasicType`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(
+    from = "BTreeMap<BasicType, Multiplier>",
+    into = "BTreeMap<BasicType, Multiplier>"
+)]
 pub struct Relationship {
-    inner: BTreeMap<BasicType, f32>,
+    inner: [Multiplier; BasicType::COUNT],
 }
 
 impl Relationship {
-    fn from_raw_parts(inner: BTreeMap<BasicType, f32>) -> Self {
-        let mut ret = Relationship { inner };
-        ret.inner.retain(|_, v| *v != 1.0);
+    /// Neutral to every type
+    pub fn neutral() -> Self {
+        Relationship {
+            inner: [Multiplier::NEUTRAL; BasicType::COUNT],
+        }
+    }
+    pub fn get(&self, key: BasicType) -> Multiplier {
+        self.inner[key as usize]
+    }
+    /// Replace every entry, e.g. for abilities that depend on the combined matchup
+    pub fn map(&self, f: impl Fn(BasicType, Multiplier) -> Multiplier) -> Relationship {
+        BasicType::iter().map(|t| (t, f(t, self.get(t)))).collect()
+    }
+    /// Non-neutral entries
+    pub fn iter(&self) -> impl Iterator<Item = (BasicType, Multiplier)> + '_ {
+        BasicType::iter()
+            .zip(self.inner)
+            .filter(|(_, v)| *v != Multiplier::NEUTRAL)
+    }
+}
+
+impl Default for Relationship {
+    fn default() -> Self {
+        Relationship::neutral()
+    }
+}
+
+impl FromIterator<(BasicType, Multiplier)> for Relationship {
+    fn from_iter<I: IntoIterator<Item = (BasicType, Multiplier)>>(iter: I) -> Self {
+        let mut ret = Relationship::neutral();
+        for (t, v) in iter {
+            ret.inner[t as usize] = v;
+        }
         ret
     }
-    pub fn get(&self, key: BasicType) -> f32 {
-        *self.inner.get(&key).unwrap_or(&1.0)
+}
+
+impl From<BTreeMap<BasicType, Multiplier>> for Relationship {
+    fn from(map: BTreeMap<BasicType, Multiplier>) -> Self {
+        map.into_iter().collect()
     }
-    pub fn iter(&self) -> impl Iterator<Item = (&BasicType, &f32)> {
-        self.inner.iter()
+}
+
+impl From<Relationship> for BTreeMap<BasicType, Multiplier> {
+    fn from(relationship: Relationship) -> Self {
+        relationship.iter().collect()
     }
 }
 
 pub fn combine_defense_charts(charts: impl IntoIterator<Item = Relationship>) -> Relationship {
-    let mut combined_chart = BTreeMap::new();
+    let mut combined_chart = Relationship::neutral();
     for chart in charts {
-        for (basic_type, multiplier) in chart.inner {
-            let entry = combined_chart.entry(basic_type).or_insert(1.0);
-            *entry *= multiplier;
+        for (entry, multiplier) in combined_chart.inner.iter_mut().zip(chart.inner) {
+            *entry = *entry * multiplier;
         }
     }
-    Relationship::from_raw_parts(combined_chart)
+    combined_chart
 }
 
 pub fn combine_defense_charts_immune(
     charts: impl IntoIterator<Item = Relationship>,
-    immune_multiplier: f32,
+    immune_multiplier: Multiplier,
 ) -> Relationship {
-    let mut combined_chart = BTreeMap::new();
+    let mut combined_chart = Relationship::neutral();
     for chart in charts {
-        for t in BasicType::iter() {
-            let entry = combined_chart.entry(t).or_insert(1.0);
-            *entry *= if chart.get(t) == 0.0 {
-                immune_multiplier
-            } else {
-                chart.get(t)
-            };
+        for (entry, multiplier) in combined_chart.inner.iter_mut().zip(chart.inner) {
+            *entry = *entry
+                * if multiplier.is_immune() {
+                    immune_multiplier
+                } else {
+                    multiplier
+                };
+        }
+    }
+    combined_chart
+}
+
+pub fn get_multitype_defense_chart<'a>(
+    types: impl Iterator<Item = &'a Type>,
+    chart: &TypeChart,
+) -> Relationship {
+    combine_defense_charts(types.map(|t| t.defense_with(chart)))
+}
+
+/// Why a type chart file could not be loaded
+#[derive(Debug)]
+pub enum ChartError {
+    Read(std::io::Error),
+    Parse(serde_json::Error),
+}
+
+impl fmt::Display for ChartError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChartError::Read(e) => write!(f, "could not read the file: {e}"),
+            ChartError::Parse(e) => write!(f, "invalid type chart: {e}"),
         }
     }
-    Relationship::from_raw_parts(combined_chart)
 }
 
-pub fn get_multitype_defense_chart<'a>(types: impl Iterator<Item = &'a Type>) -> Relationship {
-    combine_defense_charts(types.map(|t| t.defense()))
+impl std::error::Error for ChartError {}
+
+/// Defensive type chart, keyed by defending type or ability then by attacking type.
+/// Matchups that are not listed are neutral.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TypeChart {
+    types: BTreeMap<BasicType, Relationship>,
+    #[serde(default)]
+    abilities: BTreeMap<Ability, Relationship>,
+}
+
+impl TypeChart {
+    /// The chart of the default generation
+    pub fn builtin() -> &'static TypeChart {
+        Generation::default().chart()
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<TypeChart> {
+        serde_json::from_str(json)
+    }
+
+    /// Loads a chart from a JSON file, see `data/type_chart_gen6+.json` for the format
+    pub fn from_path(path: impl AsRef<Path>) -> Result<TypeChart, ChartError> {
+        let json = std::fs::read_to_string(path).map_err(ChartError::Read)?;
+        TypeChart::from_json(&json).map_err(ChartError::Parse)
+    }
+
+    /// Inverse Battle chart: weaknesses and resistances swap and type immunities become weaknesses.
+    /// Abilities are unaffected.
+    pub fn inverse(&self) -> TypeChart {
+        let types = self
+            .types
+            .iter()
+            .map(|(t, relationship)| {
+                let inverted = relationship
+                    .iter()
+                    .map(|(attacker, m)| {
+                        (
+                            attacker,
+                            m.reciprocal().unwrap_or(Multiplier::SUPER_EFFECTIVE),
+                        )
+                    })
+                    .collect();
+                (*t, inverted)
+            })
+            .collect();
+        TypeChart {
+            types,
+            abilities: self.abilities.clone(),
+        }
+    }
+
+    /// Chart where Ghost's immunities to Normal and Fighting are lifted, as with Scrappy or Foresight
+    pub fn identified(&self) -> TypeChart {
+        self.clone().map_type(BasicType::Ghost, |t, m| match t {
+            BasicType::Normal | BasicType::Fighting if m.is_immune() => Multiplier::NEUTRAL,
+            _ => m,
+        })
+    }
+
+    /// Chart where Flying types and Levitate are hit by Ground, as under Gravity
+    pub fn grounded(&self) -> TypeChart {
+        let mut chart = self.clone().map_type(BasicType::Flying, |t, m| match t {
+            BasicType::Ground if m.is_immune() => Multiplier::NEUTRAL,
+            _ => m,
+        });
+        chart.abilities.remove(&Ability::Levitate);
+        chart
+    }
+
+    /// Replace the entries of a defending type
+    pub fn map_type(
+        mut self,
+        defending: BasicType,
+        f: impl Fn(BasicType, Multiplier) -> Multiplier,
+    ) -> TypeChart {
+        if let Some(relationship) = self.types.get_mut(&defending) {
+            *relationship = relationship.map(f);
+        }
+        self
+    }
+
+    /// Types that exist in this chart
+    pub fn types(&self) -> impl Iterator<Item = BasicType> + '_ {
+        self.types.keys().copied()
+    }
+
+    pub fn defense(&self, t: &Type) -> Relationship {
+        match t {
+            Type::Basic(t) => self.types.get(t),
+            Type::Ability(a) => self.abilities.get(a),
+        }
+        .copied()
+        .unwrap_or_default()
+    }
 }
 
-fn get_defense_chart() -> &'static BTreeMap<Type, BTreeMap<BasicType, f32>> {
-    static DEFENSE_CHART: OnceLock<BTreeMap<Type, BTreeMap<BasicType, f32>>> = OnceLock::new();
-    DEFENSE_CHART.get_or_init(|| {
-        BTreeMap::from([
-            (
-                Type::Basic(BasicType::Normal),
-                BTreeMap::from([((BasicType::Fighting), 2.0), ((BasicType::Ghost), 0.0)]),
-            ),
-            (
-                Type::Basic(BasicType::Fire),
-                BTreeMap::from([
-                    ((BasicType::Fire), 0.5),
-                    ((BasicType::Water), 2.0),
-                    ((BasicType::Grass), 0.5),
-                    ((BasicType::Ice), 0.5),
-                    ((BasicType::Ground), 2.0),
-                    ((BasicType::Bug), 0.5),
-                    ((BasicType::Rock), 2.0),
-                    ((BasicType::Steel), 0.5),
-                    #[cfg(feature = "gen6")]
-                    ((BasicType::Fairy), 0.5),
-                ]),
-            ),
-            (
-                Type::Basic(BasicType::Water),
-                BTreeMap::from([
-                    ((BasicType::Fire), 0.5),
-                    ((BasicType::Water), 0.5),
-                    ((BasicType::Electric), 2.0),
-                    ((BasicType::Grass), 2.0),
-                    ((BasicType::Ice), 0.5),
-                    ((BasicType::Steel), 0.5),
-                ]),
-            ),
-            (
-                Type::Basic(BasicType::Electric),
-                BTreeMap::from([
-                    ((BasicType::Electric), 0.5),
-                    ((BasicType::Ground), 2.0),
-                    ((BasicType::Flying), 0.5),
-                    ((BasicType::Steel), 0.5),
-                ]),
-            ),
-            (
-                Type::Basic(BasicType::Grass),
-                BTreeMap::from([
-                    ((BasicType::Fire), 2.0),
-                    ((BasicType::Water), 0.5),
-                    ((BasicType::Electric), 0.5),
-                    ((BasicType::Grass), 0.5),
-                    ((BasicType::Ice), 2.0),
-                    ((BasicType::Poison), 2.0),
-                    ((BasicType::Ground), 0.5),
-                    ((BasicType::Flying), 2.0),
-                    ((BasicType::Bug), 2.0),
-                ]),
-            ),
-            (
-                Type::Basic(BasicType::Ice),
-                BTreeMap::from([
-                    ((BasicType::Fire), 2.0),
-                    ((BasicType::Ice), 0.5),
-                    ((BasicType::Fighting), 2.0),
-                    ((BasicType::Rock), 2.0),
-                    ((BasicType::Steel), 2.0),
-                ]),
-            ),
-            (
-                Type::Basic(BasicType::Fighting),
-                BTreeMap::from([
-                    ((BasicType::Flying), 2.0),
-                    ((BasicType::Psychic), 2.0),
-                    ((BasicType::Bug), 0.5),
-                    ((BasicType::Rock), 0.5),
-                    ((BasicType::Dark), 0.5),
-                    #[cfg(feature = "gen6")]
-                    ((BasicType::Fairy), 2.0),
-                ]),
-            ),
-            (
-                Type::Basic(BasicType::Poison),
-                BTreeMap::from([
-                    ((BasicType::Grass), 0.5),
-                    ((BasicType::Fighting), 0.5),
-                    ((BasicType::Poison), 0.5),
-                    ((BasicType::Ground), 2.0),
-                    ((BasicType::Psychic), 2.0),
-                    ((BasicType::Bug), 0.5),
-                    #[cfg(feature = "gen6")]
-                    ((BasicType::Fairy), 0.5),
-                ]),
-            ),
-            (
-                Type::Basic(BasicType::Ground),
-                BTreeMap::from([
-                    ((BasicType::Water), 2.0),
-                    ((BasicType::Electric), 0.0),
-                    ((BasicType::Grass), 2.0),
-                    ((BasicType::Ice), 2.0),
-                    ((BasicType::Poison), 0.5),
-                    ((BasicType::Rock), 0.5),
-                ]),
-            ),
-            (
-                Type::Basic(BasicType::Flying),
-                BTreeMap::from([
-                    ((BasicType::Electric), 2.0),
-                    ((BasicType::Grass), 0.5),
-                    ((BasicType::Ice), 2.0),
-                    ((BasicType::Fighting), 0.5),
-                    ((BasicType::Ground), 0.0),
-                    ((BasicType::Bug), 0.5),
-                    ((BasicType::Rock), 2.0),
-                ]),
-            ),
-            (
-                Type::Basic(BasicType::Psychic),
-                BTreeMap::from([
-                    ((BasicType::Fighting), 0.5),
-                    ((BasicType::Psychic), 0.5),
-                    ((BasicType::Bug), 2.0),
-                    ((BasicType::Ghost), 2.0),
-                    ((BasicType::Dark), 2.0),
-                ]),
-            ),
-            (
-                Type::Basic(BasicType::Bug),
-                BTreeMap::from([
-                    ((BasicType::Fire), 2.0),
-                    ((BasicType::Grass), 0.5),
-                    ((BasicType::Fighting), 0.5),
-                    ((BasicType::Ground), 0.5),
-                    ((BasicType::Flying), 2.0),
-                    ((BasicType::Rock), 2.0),
-                ]),
-            ),
-            (
-                Type::Basic(BasicType::Rock),
-                BTreeMap::from([
-                    ((BasicType::Normal), 0.5),
-                    ((BasicType::Fire), 0.5),
-                    ((BasicType::Water), 2.0),
-                    ((BasicType::Grass), 2.0),
-                    ((BasicType::Fighting), 2.0),
-                    ((BasicType::Poison), 0.5),
-                    ((BasicType::Ground), 2.0),
-                    ((BasicType::Flying), 0.5),
-                    ((BasicType::Steel), 2.0),
-                ]),
-            ),
-            (
-                Type::Basic(BasicType::Ghost),
-                BTreeMap::from([
-                    ((BasicType::Normal), 0.0),
-                    ((BasicType::Fighting), 0.0),
-                    ((BasicType::Poison), 0.5),
-                    ((BasicType::Bug), 0.5),
-                    ((BasicType::Ghost), 2.0),
-                    ((BasicType::Dark), 2.0),
-                ]),
-            ),
-            (
-                Type::Basic(BasicType::Dragon),
-                BTreeMap::from([
-                    ((BasicType::Fire), 0.5),
-                    ((BasicType::Water), 0.5),
-                    ((BasicType::Electric), 0.5),
-                    ((BasicType::Grass), 0.5),
-                    ((BasicType::Ice), 2.0),
-                    ((BasicType::Dragon), 2.0),
-                    #[cfg(feature = "gen6")]
-                    ((BasicType::Fairy), 2.0),
-                ]),
-            ),
-            (
-                Type::Basic(BasicType::Dark),
-                BTreeMap::from([
-                    ((BasicType::Fighting), 2.0),
-                    ((BasicType::Psychic), 0.0),
-                    ((BasicType::Bug), 2.0),
-                    ((BasicType::Ghost), 0.5),
-                    ((BasicType::Dark), 0.5),
-                    #[cfg(feature = "gen6")]
-                    ((BasicType::Fairy), 2.0),
-                ]),
-            ),
-            (
-                Type::Basic(BasicType::Steel),
-                BTreeMap::from([
-                    ((BasicType::Normal), 0.5),
-                    ((BasicType::Fire), 2.0),
-                    ((BasicType::Grass), 0.5),
-                    ((BasicType::Ice), 0.5),
-                    ((BasicType::Fighting), 2.0),
-                    ((BasicType::Poison), 0.0),
-                    ((BasicType::Ground), 2.0),
-                    ((BasicType::Flying), 0.5),
-                    ((BasicType::Psychic), 0.5),
-                    ((BasicType::Bug), 0.5),
-                    ((BasicType::Rock), 0.5),
-                    ((BasicType::Dragon), 0.5),
-                    ((BasicType::Steel), 0.5),
-                    #[cfg(feature = "gen6")]
-                    ((BasicType::Fairy), 0.5),
-                ]),
-            ),
-            #[cfg(feature = "gen6")]
-            (
-                Type::Basic(BasicType::Fairy),
-                BTreeMap::from([
-                    ((BasicType::Fighting), 0.5),
-                    ((BasicType::Poison), 2.0),
-                    ((BasicType::Bug), 0.5),
-                    ((BasicType::Dragon), 0.0),
-                    ((BasicType::Dark), 0.5),
-                    ((BasicType::Steel), 2.0),
-                ]),
-            ),
-            (
-                Type::Ability(Ability::Levitate),
-                BTreeMap::from([((BasicType::Ground), 0.0)]),
-            ),
-            (
-                Type::Ability(Ability::WaterAbsorb),
-                BTreeMap::from([((BasicType::Water), 0.0)]),
-            ),
-            (
-                Type::Ability(Ability::DrySkin),
-                BTreeMap::from([((BasicType::Water), 0.0), ((BasicType::Fire), 1.25)]),
-            ),
-            (
-                Type::Ability(Ability::FlashFire),
-                BTreeMap::from([((BasicType::Fire), 0.0)]),
-            ),
-            (
-                Type::Ability(Ability::ThickFat),
-                BTreeMap::from([((BasicType::Fire), 0.5), ((BasicType::Ice), 0.5)]),
-            ),
-            (
-                Type::Ability(Ability::VoltAbsorb),
-                BTreeMap::from([((BasicType::Electric), 0.0)]),
-            ),
-            (
-                Type::Ability(Ability::LightningRod),
-                BTreeMap::from([((BasicType::Electric), 0.0)]),
-            ),
-            (
-                Type::Ability(Ability::SapSipper),
-                BTreeMap::from([((BasicType::Grass), 0.0)]),
-            ),
-            (
-                Type::Ability(Ability::MotorDrive),
-                BTreeMap::from([((BasicType::Electric), 0.0)]),
-            ),
-            (
-                Type::Ability(Ability::StormDrain),
-                BTreeMap::from([((BasicType::Water), 0.0)]),
-            ),
-            (
-                Type::Ability(Ability::Heatproof),
-                BTreeMap::from([((BasicType::Fire), 0.5)]),
-            ),
-        ])
-    })
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pokemon::Pokemon;
+
+    #[test]
+    fn test_load_charts() {
+        let gen5 = TypeChart::from_path("data/type_chart_gen2-5.json").unwrap();
+        assert_eq!(
+            gen5.defense(&Type::Basic(BasicType::Dragon))
+                .get(BasicType::Ice),
+            2.0
+        );
+        assert_eq!(
+            TypeChart::builtin()
+                .defense(&Type::Ability(Ability::DrySkin))
+                .get(BasicType::Fire),
+            1.25
+        );
+        // Types missing from a chart are neutral to everything
+        let empty = TypeChart::from_json(r#"{"types": {}}"#).unwrap();
+        assert_eq!(BasicType::Ground.defense_with(&empty).iter().count(), 0);
+        // Invalid values are an error rather than a panic
+        assert!(TypeChart::from_json(r#"{"types": {"Fire": {"Water": -2.0}}}"#).is_err());
+        assert!(matches!(
+            TypeChart::from_path("data/no_such_chart.json"),
+            Err(ChartError::Read(_))
+        ));
+        assert!(matches!(
+            TypeChart::from_path("data/evolutions.csv"),
+            Err(ChartError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_relationship() {
+        use BasicType::*;
+        let relationship = Relationship::from_iter([
+            (Fire, Multiplier::SUPER_EFFECTIVE),
+            (Water, Multiplier::NEUTRAL),
+            (Ground, Multiplier::IMMUNE),
+        ]);
+        assert_eq!(
+            relationship.iter().collect::<Vec<_>>(),
+            vec![
+                (Fire, Multiplier::SUPER_EFFECTIVE),
+                (Ground, Multiplier::IMMUNE)
+            ]
+        );
+        let json = serde_json::to_string(&relationship).unwrap();
+        assert_eq!(json, r#"{"Fire":2.0,"Ground":0.0}"#);
+        assert_eq!(
+            serde_json::from_str::<Relationship>(&json).unwrap(),
+            relationship
+        );
+        let combined = combine_defense_charts([
+            relationship,
+            Relationship::from_iter([(Fire, Multiplier::RESISTED)]),
+        ]);
+        assert_eq!(combined.get(Fire), 1.0);
+        assert_eq!(combined.get(Ground), 0.0);
+    }
+
+    #[test]
+    fn test_abilities() {
+        use BasicType::*;
+        let with = |typing: Pokemon, ability| {
+            Pokemon {
+                ability: Some(ability),
+                ..typing
+            }
+            .defense()
+        };
+        let shedinja = with(Pokemon::from((Bug, Ghost)), Ability::WonderGuard);
+        assert_eq!(
+            shedinja
+                .iter()
+                .filter(|(_, m)| !m.is_immune())
+                .map(|(t, _)| t)
+                .collect::<Vec<_>>(),
+            vec![Fire, Flying, Rock, Ghost, Dark]
+        );
+        assert_eq!(shedinja.get(Rock), 2.0);
+        let camerupt = with(Pokemon::from((Fire, Ground)), Ability::SolidRock);
+        assert_eq!(camerupt.get(Water), Multiplier::new(3, 1));
+        assert_eq!(camerupt.get(Fire), 0.5);
+        assert_eq!(camerupt.get(Electric), 0.0);
+        let garganacl = with(Pokemon::from(Rock), Ability::PurifyingSalt);
+        assert_eq!(garganacl.get(Ghost), 0.5);
+        assert_eq!(with(Pokemon::from(Normal), Ability::Fluffy).get(Fire), 2.0);
+        assert_eq!(
+            with(Pokemon::from(Ground), Ability::EarthEater).get(Ground),
+            0.0
+        );
+        assert_eq!(
+            with(Pokemon::from(Fairy), Ability::WellBakedBody).get(Fire),
+            0.0
+        );
+        assert_eq!(
+            with(Pokemon::from(Water), Ability::WaterBubble).get(Fire),
+            0.25
+        );
+    }
+
+    #[test]
+    fn test_attacker_abilities() {
+        use BasicType::*;
+        let attacker = |typing: Pokemon, ability| Pokemon {
+            ability: Some(ability),
+            ..typing
+        };
+        let kangaskhan = attacker(Pokemon::from(Normal), Ability::Scrappy);
+        assert_eq!(kangaskhan.effectiveness(&Pokemon::from((Ghost, Rock))), 0.5);
+        assert_eq!(
+            Pokemon::from(Normal).effectiveness(&Pokemon::from((Ghost, Rock))),
+            0.0
+        );
+        let mind_eye = attacker(Pokemon::from(Fighting), Ability::MindsEye);
+        assert_eq!(mind_eye.effectiveness(&Pokemon::from((Ghost, Dark))), 2.0);
+        // Tinted Lens does not lift immunities
+        let yanmega = attacker(Pokemon::from((Bug, Flying)), Ability::TintedLens);
+        assert_eq!(yanmega.effectiveness(&Pokemon::from(Steel)), 1.0);
+        assert_eq!(yanmega.effectiveness(&Pokemon::from((Electric, Rock))), 1.0);
+        let normal_fairy = attacker(Pokemon::from((Normal, Fairy)), Ability::Pixilate);
+        assert_eq!(normal_fairy.attacking_types(), vec![Fairy]);
+        assert_eq!(normal_fairy.effectiveness(&Pokemon::from(Dragon)), 2.0);
+        let normalize = attacker(Pokemon::from((Fire, Water)), Ability::Normalize);
+        assert_eq!(normalize.attacking_types(), vec![Normal]);
+        assert_eq!(Ability::Adaptability.stab_bonus(), 2.0);
+        assert_eq!(Ability::Levitate.stab_bonus(), 1.5);
+        // Offensive abilities are not defensive types
+        assert!(!Type::iter().any(|t| t == Type::Ability(Ability::TintedLens)));
+        assert!(Type::iter().any(|t| t == Type::Ability(Ability::Levitate)));
+    }
+
+    #[test]
+    fn test_special_moves() {
+        use BasicType::*;
+        let chart = TypeChart::builtin();
+        let freeze_dry = |p: &Pokemon| SpecialMove::FreezeDry.effectiveness_with(Ice, p, chart);
+        assert_eq!(freeze_dry(&Pokemon::from((Water, Ground))), 4.0);
+        assert_eq!(freeze_dry(&Pokemon::from((Water, Flying))), 4.0);
+        assert_eq!(freeze_dry(&Pokemon::from(Steel)), 0.5);
+        let flying_press =
+            |p: &Pokemon| SpecialMove::FlyingPress.effectiveness_with(Fighting, p, chart);
+        assert_eq!(flying_press(&Pokemon::from((Grass, Normal))), 4.0);
+        assert_eq!(flying_press(&Pokemon::from(Bug)), 1.0);
+        assert_eq!(flying_press(&Pokemon::from(Ghost)), 0.0);
+        let thousand_arrows =
+            |p: &Pokemon| SpecialMove::ThousandArrows.effectiveness_with(Ground, p, chart);
+        assert_eq!(thousand_arrows(&Pokemon::from((Grass, Flying))), 1.0);
+        assert_eq!(thousand_arrows(&Pokemon::from((Fire, Flying))), 1.0);
+        // Levitate only loses its immunity
+        assert_eq!(
+            thousand_arrows(&Pokemon {
+                ability: Some(Ability::Levitate),
+                ..Pokemon::from(Steel)
+            }),
+            2.0
+        );
+        assert_eq!(
+            thousand_arrows(&Pokemon {
+                ability: Some(Ability::Levitate),
+                ..Pokemon::from((Ghost, Grass))
+            }),
+            0.5
+        );
+        assert_eq!(thousand_arrows(&Pokemon::from(Steel)), 2.0);
+        assert_eq!(
+            thousand_arrows(&Pokemon {
+                ability: Some(Ability::EarthEater),
+                ..Pokemon::from(Steel)
+            }),
+            0.0
+        );
+        assert_eq!(
+            SpecialMove::SkyDrop.effectiveness_with(Flying, &Pokemon::from((Fire, Flying)), chart),
+            0.0
+        );
+        assert_eq!(
+            SpecialMove::SkyDrop.effectiveness_with(Flying, &Pokemon::from(Grass), chart),
+            2.0
+        );
+        // Wind Rider only blocks wind moves
+        let wind_rider = Pokemon {
+            ability: Some(Ability::WindRider),
+            ..Pokemon::from((Grass, Ghost))
+        };
+        let hurricane = crate::pokemon::Move::get("Hurricane").unwrap();
+        assert_eq!(hurricane.special(), Some(SpecialMove::Wind));
+        assert_eq!(SpecialMove::Wind.effectiveness_with(Flying, &wind_rider, chart), 0.0);
+        assert_eq!(SpecialMove::Wind.effectiveness_with(Fire, &wind_rider, chart), 0.0);
+        assert_eq!(wind_rider.defense_with(chart).get(Flying), 2.0);
+        assert_eq!(
+            SpecialMove::Wind.effectiveness_with(Flying, &Pokemon::from((Grass, Ghost)), chart),
+            2.0
+        );
+    }
+
+    #[test]
+    fn test_inverse() {
+        use BasicType::*;
+        let inverse = TypeChart::builtin().inverse();
+        let gyarados = Pokemon::from((Water, Flying));
+        let defense = gyarados.defense_with(&inverse);
+        assert_eq!(defense.get(Electric), 0.25);
+        assert_eq!(defense.get(Ground), 2.0);
+        assert_eq!(defense.get(Fire), 2.0);
+        assert_eq!(defense.get(Normal), 1.0);
+        // Abilities keep their immunities
+        let levitate = Pokemon {
+            ability: Some(Ability::Levitate),
+            ..Pokemon::from(Steel)
+        };
+        assert_eq!(levitate.defense_with(&inverse).get(Ground), 0.0);
+        assert_eq!(levitate.defense_with(&inverse).get(Poison), 2.0);
+        assert_eq!(
+            inverse.inverse().types().count(),
+            TypeChart::builtin().types().count()
+        );
+    }
+
+    #[test]
+    fn test_attack() {
+        use BasicType::*;
+        assert_eq!(Ground.effectiveness(&Flying), 0.0);
+        assert_eq!(Ground.effectiveness(&Ability::Levitate), 0.0);
+        assert_eq!(Fighting.effectiveness(&Steel), 2.0);
+        let attack = Fire.attack();
+        assert_eq!(attack.get(Grass), 2.0);
+        assert_eq!(attack.get(Water), 0.5);
+        assert_eq!(attack.get(Normal), 1.0);
+        // No Steel type in gen 1
+        assert_eq!(Fire.attack_with(Generation::Gen1.chart()).iter().count(), 7);
+    }
 }
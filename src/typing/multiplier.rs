@@ -0,0 +1,277 @@
+use serde::{Deserialize, Serialize};
+use std::{cmp::Ordering, fmt, ops::Mul};
+
+/// Exact type effectiveness, either an immunity or `2^exponent * numerator / denominator`
+/// where the rational part only holds odd factors, e.g. Dry Skin's 5/4 is `2^-2 * 5`
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "f64", into = "f64")]
+pub struct Multiplier {
+    immune: bool,
+    exponent: i32,
+    numerator: u32,
+    denominator: u32,
+}
+
+impl Multiplier {
+    pub const IMMUNE: Multiplier = Multiplier {
+        immune: true,
+        exponent: 0,
+        numerator: 0,
+        denominator: 1,
+    };
+    pub const NEUTRAL: Multiplier = Multiplier::power_of_two(0);
+    pub const SUPER_EFFECTIVE: Multiplier = Multiplier::power_of_two(1);
+    pub const RESISTED: Multiplier = Multiplier::power_of_two(-1);
+
+    pub const fn power_of_two(exponent: i32) -> Multiplier {
+        Multiplier {
+            immune: false,
+            exponent,
+            numerator: 1,
+            denominator: 1,
+        }
+    }
+
+    /// Rational multiplier, a zero numerator is an immunity
+    pub fn new(numerator: u32, denominator: u32) -> Multiplier {
+        Multiplier::normalize(numerator as u64, denominator as u64, 0)
+    }
+
+    fn normalize(mut numerator: u64, mut denominator: u64, mut exponent: i32) -> Multiplier {
+        assert!(denominator != 0, "Multiplier with zero denominator");
+        if numerator == 0 {
+            return Multiplier::IMMUNE;
+        }
+        let twos = numerator.trailing_zeros();
+        numerator >>= twos;
+        exponent += twos as i32;
+        let twos = denominator.trailing_zeros();
+        denominator >>= twos;
+        exponent -= twos as i32;
+        let gcd = gcd(numerator, denominator);
+        Multiplier {
+            immune: false,
+            exponent,
+            numerator: (numerator / gcd)
+                .try_into()
+                .expect("Multiplier numerator overflow"),
+            denominator: (denominator / gcd)
+                .try_into()
+                .expect("Multiplier denominator overflow"),
+        }
+    }
+
+    pub fn is_immune(&self) -> bool {
+        self.immune
+    }
+
+    /// Power of two part, e.g. 2 for a 4x weakness, -1 for a resistance
+    pub fn exponent(&self) -> i32 {
+        self.exponent
+    }
+
+    /// Odd rational part left after the power of two, 1/1 for plain type chart values
+    pub fn modifier(&self) -> (u32, u32) {
+        (self.numerator, self.denominator)
+    }
+
+    /// `1 / self`, or `None` for an immunity
+    pub fn reciprocal(&self) -> Option<Multiplier> {
+        if self.immune {
+            return None;
+        }
+        Some(Multiplier {
+            immune: false,
+            exponent: -self.exponent,
+            numerator: self.denominator,
+            denominator: self.numerator,
+        })
+    }
+
+    pub fn log2(&self) -> f64 {
+        if self.immune {
+            return f64::NEG_INFINITY;
+        }
+        self.exponent as f64 + (self.numerator as f64 / self.denominator as f64).log2()
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.numerator as f64 / self.denominator as f64 * 2f64.powi(self.exponent)
+    }
+
+    /// `value` scaled by this multiplier and rounded down, as damage is in the games
+    pub fn apply(&self, value: u32) -> u32 {
+        if self.immune {
+            return 0;
+        }
+        let (numerator, denominator) = self.as_fraction();
+        (value as u128 * numerator / denominator) as u32
+    }
+
+    /// Numerator and denominator with the power of two applied
+    fn as_fraction(&self) -> (u128, u128) {
+        let shift = self.exponent.unsigned_abs();
+        assert!(shift < 64, "Multiplier exponent out of range");
+        if self.exponent >= 0 {
+            ((self.numerator as u128) << shift, self.denominator as u128)
+        } else {
+            (self.numerator as u128, (self.denominator as u128) << shift)
+        }
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+impl Default for Multiplier {
+    fn default() -> Self {
+        Multiplier::NEUTRAL
+    }
+}
+
+impl Mul for Multiplier {
+    type Output = Multiplier;
+
+    fn mul(self, rhs: Multiplier) -> Multiplier {
+        if self.immune || rhs.immune {
+            return Multiplier::IMMUNE;
+        }
+        Multiplier::normalize(
+            self.numerator as u64 * rhs.numerator as u64,
+            self.denominator as u64 * rhs.denominator as u64,
+            self.exponent + rhs.exponent,
+        )
+    }
+}
+
+impl Ord for Multiplier {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.immune, other.immune) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            (false, false) => {
+                let (n1, d1) = self.as_fraction();
+                let (n2, d2) = other.as_fraction();
+                (n1 * d2).cmp(&(n2 * d1))
+            }
+        }
+    }
+}
+
+impl PartialOrd for Multiplier {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq<f64> for Multiplier {
+    fn eq(&self, other: &f64) -> bool {
+        self.to_f64() == *other
+    }
+}
+
+/// A negative, infinite or NaN multiplier
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InvalidMultiplier(pub f64);
+
+impl fmt::Display for InvalidMultiplier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid multiplier {}", self.0)
+    }
+}
+
+/// Closest rational with a small denominator, so that chart values like 1.2 become 6/5
+impl TryFrom<f64> for Multiplier {
+    type Error = InvalidMultiplier;
+
+    fn try_from(value: f64) -> Result<Multiplier, InvalidMultiplier> {
+        if !(value >= 0.0 && value.is_finite()) {
+            return Err(InvalidMultiplier(value));
+        }
+        const MAX_DENOMINATOR: u64 = 1 << 16;
+        // Continued fraction expansion
+        let (mut h0, mut h1, mut k0, mut k1) = (0u64, 1u64, 1u64, 0u64);
+        let mut x = value;
+        loop {
+            let a = x.floor();
+            let h2 = a as u64 * h1 + h0;
+            let k2 = a as u64 * k1 + k0;
+            if k2 > MAX_DENOMINATOR {
+                break;
+            }
+            (h0, h1, k0, k1) = (h1, h2, k1, k2);
+            if (h1 as f64 / k1 as f64 - value).abs() < 1e-9 {
+                break;
+            }
+            x = 1.0 / (x - a);
+        }
+        Ok(Multiplier::normalize(h1, k1, 0))
+    }
+}
+
+impl From<Multiplier> for f64 {
+    fn from(multiplier: Multiplier) -> f64 {
+        multiplier.to_f64()
+    }
+}
+
+impl fmt::Display for Multiplier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (numerator, denominator) = self.as_fraction();
+        if denominator == 1 {
+            write!(f, "{numerator}")
+        } else {
+            write!(f, "{numerator}/{denominator}")
+        }
+    }
+}
+
+impl fmt::Debug for Multiplier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "x{self}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_f64() {
+        let from = |value: f64| Multiplier::try_from(value).unwrap();
+        assert_eq!(from(0.0), Multiplier::IMMUNE);
+        assert_eq!(from(1.0), Multiplier::NEUTRAL);
+        assert_eq!(from(0.25), Multiplier::power_of_two(-2));
+        let dry_skin = from(1.25);
+        assert_eq!(dry_skin.exponent(), -2);
+        assert_eq!(dry_skin.modifier(), (5, 1));
+        assert_eq!(from(1.2), Multiplier::new(6, 5));
+        assert_eq!(Multiplier::try_from(-1.0), Err(InvalidMultiplier(-1.0)));
+        assert!(Multiplier::try_from(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let dry_skin = Multiplier::new(5, 4);
+        assert!(dry_skin > Multiplier::NEUTRAL);
+        assert!(dry_skin < Multiplier::SUPER_EFFECTIVE);
+        assert_eq!(dry_skin * Multiplier::RESISTED, Multiplier::new(5, 8));
+        assert_eq!(Multiplier::new(4, 5) * dry_skin, Multiplier::NEUTRAL);
+        assert_eq!(dry_skin * Multiplier::IMMUNE, Multiplier::IMMUNE);
+        assert!(Multiplier::IMMUNE < Multiplier::power_of_two(-10));
+        assert_eq!(dry_skin.reciprocal(), Some(Multiplier::new(4, 5)));
+        assert_eq!(Multiplier::IMMUNE.reciprocal(), None);
+        assert_eq!(dry_skin.to_string(), "5/4");
+        assert_eq!(Multiplier::power_of_two(2).to_string(), "4");
+        assert_eq!(Multiplier::new(3, 2).apply(45), 67);
+        assert_eq!(Multiplier::new(6, 5).apply(5), 6);
+        assert_eq!(Multiplier::power_of_two(-2).apply(45), 11);
+        assert_eq!(Multiplier::IMMUNE.apply(45), 0);
+    }
+}